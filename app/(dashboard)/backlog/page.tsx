@@ -1,13 +1,19 @@
 "use client";
 
 import { useState } from "react";
+import { useRouter, useSearchParams } from "next/navigation";
 import { Header } from "@/components/layout/header";
 import { BacklogContent } from "@/components/backlog/backlog-content";
 import { BacklogFilterToggle } from "@/components/backlog/backlog-filter-toggle";
 
 export default function BacklogPage() {
+  const router = useRouter();
+  const searchParams = useSearchParams();
   const [showAllTasks, setShowAllTasks] = useState(true);
 
+  // ?task=<id> opens that task (deep link miniorg://task/<id>)
+  const taskParam = searchParams.get("task");
+
   return (
     <>
       <div className="flex flex-col h-full">
@@ -26,6 +32,8 @@ export default function BacklogPage() {
           <BacklogContent 
             showAllTasks={showAllTasks}
             onShowAllTasksChange={setShowAllTasks}
+            openTaskId={taskParam}
+            onTaskOpened={() => router.replace("/backlog")}
           />
         </div>
       </div>
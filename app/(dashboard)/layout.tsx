@@ -19,10 +19,10 @@ import { useTauriTaskHandler } from "@/lib/hooks/use-tauri-task-handler";
 import { QuickAddWindow } from "@/components/layout/quick-add-window";
 import { isTauri } from "@/lib/platform";
 import { listen, TauriEvents } from "@/lib/tauri/events";
+import { notifyDeepLinkClosed, notifyDeepLinkReady } from "@/lib/deep-link-tauri";
 import { TimelineDragProvider } from "@/lib/contexts/timeline-drag-context";
 import { TimelineDateProvider } from "@/lib/contexts/timeline-date-context";
 import { DailyHighlightBanner } from "@/components/daily-planning/daily-highlight-banner";
-import { onOpenUrl } from "@tauri-apps/plugin-deep-link";
 function DashboardContentInner({ children }: { children: React.ReactNode }) {
   const [isSidebarCollapsed, setIsSidebarCollapsed] = useState(false);
   const { activePanel } = useRightSidebar();
@@ -36,50 +36,38 @@ function DashboardContentInner({ children }: { children: React.ReactNode }) {
   // Handle task creation requests from quick-add window
   useTauriTaskHandler();
 
-  // Listen for navigation events from other Tauri windows
+  // Listen for navigation events from other Tauri windows and deep links
+  // (e.g., miniorg://settings/calendars?onboarding=true), which are routed in Rust:
+  // it emits navigate-to, and deep-link-error for unknown links.
+  // Events for links received while these listeners don't exist are queued in Rust.
   useEffect(() => {
-    let unlisten: (() => void) | null = null;
-
-    const setupListener = async () => {
-      unlisten = await listen(TauriEvents.NAVIGATE_TO, (path) => {
-        router.push(path);
-      });
-    };
-
-    setupListener();
-
-    return () => {
-      if (unlisten) unlisten();
-    };
-  }, [router]);
+    let cancelled = false;
+    const unlisteners: (() => void)[] = [];
+
+    const setupListeners = async () => {
+      unlisteners.push(
+        await listen(TauriEvents.NAVIGATE_TO, (path) => {
+          router.push(path);
+        }),
+        await listen(TauriEvents.DEEP_LINK_ERROR, (error) => {
+          console.error("[Deep Link]", error);
+          toast.error("Lien non reconnu", { description: error });
+        })
+      );
 
-  // Listen for deep links (e.g., miniorg://settings/calendars?onboarding=true)
-  useEffect(() => {
-    if (!isTauri()) return;
-
-    let unlisten: (() => void) | null = null;
-
-    const setupDeepLinkListener = async () => {
-      unlisten = await onOpenUrl((urls) => {
-        for (const url of urls) {
-          try {
-            // Parse the deep link URL: miniorg://path?query
-            const parsed = new URL(url);
-            const path = parsed.pathname || parsed.host; // pathname for miniorg://path, host for miniorg://host
-            const search = parsed.search;
-            const fullPath = `/${path}${search}`;
-            router.push(fullPath);
-          } catch (e) {
-            console.error("[Deep Link] Failed to parse URL:", url, e);
-          }
-        }
-      });
+      if (cancelled) {
+        unlisteners.forEach((unlisten) => unlisten());
+        return;
+      }
+      await notifyDeepLinkReady();
     };
 
-    setupDeepLinkListener();
+    setupListeners();
 
     return () => {
-      if (unlisten) unlisten();
+      cancelled = true;
+      unlisteners.forEach((unlisten) => unlisten());
+      notifyDeepLinkClosed();
     };
   }, [router]);
 
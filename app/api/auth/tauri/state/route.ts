@@ -39,7 +39,8 @@ export async function POST(req: NextRequest) {
     const secret = getTauriJwtSecret();
     const issuer = getTauriJwtIssuer();
     const audience = getTauriStateAudience();
-    const expiresInSeconds = 5 * 60;
+    // Sent as the OAuth state, so it must outlive a login (the app waits 10 minutes)
+    const expiresInSeconds = 10 * 60;
 
     const state = await new SignJWT({
       nonce: crypto.randomUUID(),
@@ -1,6 +1,7 @@
 "use client";
 
-import { useState } from "react";
+import { useEffect, useRef, useState } from "react";
+import { toast } from "sonner";
 import { BacklogGroups } from "@/components/backlog/backlog-groups";
 import { EditTaskDialog } from "@/components/tasks/edit-task-dialog";
 import { useTasksQuery } from "@/lib/api/queries/tasks";
@@ -13,19 +14,40 @@ type BacklogContentProps = {
   compact?: boolean;
   showAllTasks?: boolean;
   onShowAllTasksChange?: (value: boolean) => void;
+  /** Task to open in the edit dialog once tasks are loaded (e.g. from a miniorg://task link) */
+  openTaskId?: string | null;
+  onTaskOpened?: () => void;
 };
 
 export function BacklogContent({ 
   showHeader = false,
   compact = false,
   showAllTasks = true,
-  onShowAllTasksChange
+  onShowAllTasksChange,
+  openTaskId,
+  onTaskOpened,
 }: BacklogContentProps) {
   const [editingTask, setEditingTask] = useState<Task | null>(null);
   const [isEditDialogOpen, setIsEditDialogOpen] = useState(false);
+  const handledTaskId = useRef<string | null>(null);
 
   // Use React Query hooks
   const { data: allTasks = [], isLoading } = useTasksQuery();
+
+  // Open the requested task once, as soon as the tasks are loaded
+  useEffect(() => {
+    if (!openTaskId || isLoading || handledTaskId.current === openTaskId) return;
+    handledTaskId.current = openTaskId;
+
+    const task = allTasks.find((t: Task) => t.id === openTaskId);
+    if (task) {
+      setEditingTask(task);
+      setIsEditDialogOpen(true);
+    } else {
+      toast.error("Task not found");
+    }
+    onTaskOpened?.();
+  }, [openTaskId, isLoading, allTasks, onTaskOpened]);
   const updateTask = useUpdateTaskMutation();
   const deleteTask = useDeleteTaskMutation();
 
@@ -2,20 +2,20 @@
 
 import { useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { listen, TauriEvents } from "@/lib/tauri/events";
+import { listen, TauriEvents, type OpenQuickAddPayload } from "@/lib/tauri/events";
 import { useQuickAddTask } from "@/providers/quick-add-task";
 import { QuickAddTask } from "@/components/tasks/quick-add-task";
 
 /**
  * Hook to listen for Tauri events that trigger opening the quick add modal
  */
-function useQuickAddListener(onOpen: () => void) {
+function useQuickAddListener(onOpen: (payload: OpenQuickAddPayload | null) => void) {
   useEffect(() => {
     let unlisten: (() => void) | null = null;
 
     const setupListener = async () => {
-      unlisten = await listen(TauriEvents.OPEN_QUICK_ADD, () => {
-        onOpen();
+      unlisten = await listen(TauriEvents.OPEN_QUICK_ADD, (payload) => {
+        onOpen(payload);
       });
     };
 
@@ -37,9 +37,9 @@ export function QuickAddWindow() {
   const { isOpen, openQuickAdd } = useQuickAddTask();
   const prevIsOpen = useRef(isOpen);
 
-  // Listen for Tauri events to open the modal
-  useQuickAddListener(() => {
-    openQuickAdd();
+  // Listen for Tauri events to open the modal (pre-filled by miniorg://add?title=...)
+  useQuickAddListener((payload) => {
+    openQuickAdd(undefined, payload?.title ?? undefined);
   });
 
   // Handle window visibility when modal closes
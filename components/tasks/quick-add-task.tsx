@@ -26,7 +26,7 @@ type QuickAddTaskProps = {
 };
 
 export function QuickAddTask({ onTaskCreated, hideButton, hideHints, disableDatePickerPortal }: QuickAddTaskProps) {
-  const { isOpen, prefilledDate, openQuickAdd, closeQuickAdd } = useQuickAddTask();
+  const { isOpen, prefilledDate, prefilledTitle, openQuickAdd, closeQuickAdd } = useQuickAddTask();
   const { isTauri } = usePlatform();
   const [title, setTitle] = useState("");
   const [deadlineType, setDeadlineType] = useState<string>("next_3_days");
@@ -126,6 +126,13 @@ export function QuickAddTask({ onTaskCreated, hideButton, hideHints, disableDate
     }
   }, [isOpen, prefilledDate]);
 
+  // Titre prérempli (ex. miniorg://add?title=...), à confirmer par l'utilisateur
+  useEffect(() => {
+    if (isOpen && prefilledTitle) {
+      setTitle(prefilledTitle);
+    }
+  }, [isOpen, prefilledTitle]);
+
   useEffect(() => {
     const down = (e: KeyboardEvent) => {
       if ((e.key === "k" && (e.metaKey || e.ctrlKey))) {
@@ -4,8 +4,13 @@
  */
 
 import { ApiClient } from "@/lib/api/client";
-import { getApiUrl, isTauri } from "@/lib/platform";
-import { listen, TauriEvents, type OAuthCodeReceivedPayload } from "@/lib/tauri/events";
+import { getApiBaseUrl, getApiUrl, isTauri } from "@/lib/platform";
+import {
+  listen,
+  TauriEvents,
+  type AuthTokenRefreshedPayload,
+  type OAuthCodeReceivedPayload,
+} from "@/lib/tauri/events";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-shell";
 
@@ -22,58 +27,32 @@ export interface TauriSession {
   expiresAt: number;
 }
 
-// Track the last redirect URI used so we can reuse it when exchanging the code
-let lastRedirectUri: string | null = null;
-let lastCodeVerifier: string | null = null;
-let lastState: string | null = null;
-
 type StoredAuthToken = {
   token: string;
   expires_at?: number | null;
 };
 
-function base64UrlEncode(bytes: Uint8Array): string {
-  let binary = "";
-  for (let i = 0; i < bytes.length; i += 1) {
-    binary += String.fromCharCode(bytes[i]);
-  }
-  const base64 = btoa(binary);
-  return base64.replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
-}
-
-async function sha256(input: string): Promise<Uint8Array> {
-  const data = new TextEncoder().encode(input);
-  const digest = await crypto.subtle.digest("SHA-256", data);
-  return new Uint8Array(digest);
-}
-
-async function createCodeVerifier(): Promise<string> {
-  const bytes = new Uint8Array(32);
-  crypto.getRandomValues(bytes);
-  return base64UrlEncode(bytes);
-}
-
-async function createCodeChallenge(verifier: string): Promise<string> {
-  const hashed = await sha256(verifier);
-  return base64UrlEncode(hashed);
-}
-
-async function fetchOAuthState(): Promise<string> {
-  const response = await fetch(getApiUrl("/api/auth/tauri/state"), {
-    method: "POST",
-    headers: {
-      "Content-Type": "application/json",
-    },
-    credentials: "include",
-  });
+type OAuthSessionResponse = {
+  token: string;
+  expires_at: number;
+  user: TauriUser;
+};
 
-  if (!response.ok) {
-    const error = await response.json();
-    throw new Error(error.error || "Failed to create OAuth state");
-  }
+/**
+ * Error rejected by the Rust auth commands (`AuthError`)
+ */
+export type TauriAuthError = {
+  kind: "not_found" | "locked" | "denied" | "corrupt" | "backend";
+  message: string;
+};
 
-  const data = await response.json();
-  return data.state as string;
+export function isTauriAuthError(error: unknown): error is TauriAuthError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    typeof (error as TauriAuthError).kind === "string" &&
+    typeof (error as TauriAuthError).message === "string"
+  );
 }
 
 /**
@@ -85,9 +64,8 @@ async function getLoopbackRedirectUri(): Promise<string> {
     throw new Error("Loopback OAuth redirect only available in Tauri");
   }
 
-  const redirectUri = await invoke<string>("start_oauth_listener");
-  lastRedirectUri = redirectUri;
-  return redirectUri;
+  // The page shown in the browser after the redirect follows the app language
+  return await invoke<string>("start_oauth_listener", { locale: navigator.language });
 }
 
 /**
@@ -102,106 +80,55 @@ export async function startTauriOAuthFlow(): Promise<void> {
 
   // Start loopback listener in Rust and get the redirect URI (http://127.0.0.1:<port>/callback)
   const redirectUri = await getLoopbackRedirectUri();
-  const state = await fetchOAuthState();
-  const codeVerifier = await createCodeVerifier();
-  const codeChallenge = await createCodeChallenge(codeVerifier);
 
-  lastCodeVerifier = codeVerifier;
-  lastState = state;
-
-  console.info("[tauri-auth] oauth start", {
+  // Rust gets a signed state from the server, mints the PKCE verifier and keeps it to itself
+  const authUrl = await invoke<string>("build_oauth_url", {
+    apiUrl: getApiBaseUrl(),
+    clientId,
     redirectUri,
-    statePrefix: state.slice(0, 8),
-    codeChallengePrefix: codeChallenge.slice(0, 8),
-    codeVerifierLength: codeVerifier.length,
   });
 
-  // Build OAuth URL
-  const scope = [
-    "openid",
-    "email",
-    "profile",
-    "https://www.googleapis.com/auth/calendar.readonly",
-    "https://www.googleapis.com/auth/calendar.events",
-  ].join(" ");
-
-  const authUrl = new URL("https://accounts.google.com/o/oauth2/v2/auth");
-  authUrl.searchParams.set("client_id", clientId);
-  authUrl.searchParams.set("redirect_uri", redirectUri);
-  authUrl.searchParams.set("response_type", "code");
-  authUrl.searchParams.set("scope", scope);
-  authUrl.searchParams.set("access_type", "offline");
-  authUrl.searchParams.set("prompt", "consent");
-  authUrl.searchParams.set("state", state);
-  authUrl.searchParams.set("code_challenge", codeChallenge);
-  authUrl.searchParams.set("code_challenge_method", "S256");
+  console.info("[tauri-auth] oauth start", { redirectUri });
 
   // Open in default browser using Tauri's shell
   try {
-    await open(authUrl.toString());
+    await open(authUrl);
   } catch (error) {
     console.error("Failed to open OAuth URL:", error);
     // Fallback to window.open
-    window.open(authUrl.toString(), "_blank");
+    window.open(authUrl, "_blank");
   }
 }
 
 /**
- * Exchange OAuth code for JWT token
+ * Abandon the pending OAuth flow - the listener reports a "cancelled" OAuth error
+ */
+export async function cancelTauriOAuthFlow(): Promise<void> {
+  if (!isTauri()) return;
+  await invoke("cancel_oauth_listener");
+}
+
+/**
+ * Exchange OAuth code for JWT token.
+ * The Rust side looks up the flow by its state and sends the stored PKCE verifier.
  */
 export async function exchangeCodeForToken(
   code: string,
-  redirectUriOverride?: string,
-  stateOverride?: string
+  state: string
 ): Promise<TauriSession> {
-  const redirectUri = redirectUriOverride || lastRedirectUri;
-  const codeVerifier = lastCodeVerifier;
-  const state = stateOverride || lastState;
-
-  if (!redirectUri) {
-    throw new Error("Missing redirect URI for token exchange");
-  }
-
-  if (!codeVerifier) {
-    throw new Error("Missing PKCE code verifier for token exchange");
-  }
-
-  if (!state) {
-    throw new Error("Missing OAuth state for token exchange");
-  }
-
   console.info("[tauri-auth] exchange code", {
     codePrefix: code.slice(0, 8),
-    redirectUri,
     statePrefix: state.slice(0, 8),
-    codeVerifierLength: codeVerifier.length,
   });
 
-  const response = await fetch(getApiUrl("/api/auth/tauri/token"), {
-    method: "POST",
-    headers: {
-      "Content-Type": "application/json",
-    },
-    credentials: "include",
-    body: JSON.stringify({
-      code,
-      redirect_uri: redirectUri,
-      code_verifier: codeVerifier,
-      state,
-    }),
+  const data = await invoke<OAuthSessionResponse>("exchange_oauth_code", {
+    apiUrl: getApiBaseUrl(),
+    code,
+    state,
   });
 
-  if (!response.ok) {
-    const error = await response.json();
-    throw new Error(error.error || "Failed to exchange code for token");
-  }
-
-  const data = await response.json();
-
   // Store token in ApiClient
   ApiClient.setAuthToken(data.token);
-  lastCodeVerifier = null;
-  lastState = null;
 
   return {
     user: data.user,
@@ -242,7 +169,30 @@ export async function refreshTauriSession(): Promise<TauriSession | null> {
 }
 
 /**
- * Get current Tauri session from secure storage
+ * Start the Rust token manager, which refreshes the JWT before it expires.
+ * Returns a cleanup function for the refresh listener.
+ */
+export async function startTauriTokenRefresh(): Promise<() => void> {
+  if (typeof window === "undefined" || !isTauri()) return () => {};
+
+  await invoke("start_token_refresh", { apiUrl: getApiBaseUrl() });
+  return await listen(TauriEvents.AUTH_TOKEN_REFRESHED, (payload: AuthTokenRefreshedPayload) => {
+    ApiClient.setAuthToken(payload.token);
+  });
+}
+
+/**
+ * Whether a credential store error leaves the session unknown rather than gone:
+ * the keychain is locked or failing, so the user is not logged out
+ */
+export function isTauriStorageUnavailable(error: unknown): error is TauriAuthError {
+  return isTauriAuthError(error) && (error.kind === "locked" || error.kind === "backend");
+}
+
+/**
+ * Get current Tauri session from secure storage.
+ * Throws the `TauriAuthError` when the store is locked or failing (see
+ * `isTauriStorageUnavailable`) - that must not be treated as a logout.
  */
 export async function getTauriSession(): Promise<TauriSession | null> {
   if (typeof window === "undefined" || !isTauri()) return null;
@@ -251,7 +201,12 @@ export async function getTauriSession(): Promise<TauriSession | null> {
   try {
     stored = await invoke<StoredAuthToken | null>("get_auth_token");
   } catch (error) {
-    console.error("[tauri-auth] Keychain access error:", error);
+    if (isTauriAuthError(error)) {
+      console.error(`[tauri-auth] Keychain access error (${error.kind}):`, error.message);
+    } else {
+      console.error("[tauri-auth] Keychain access error:", error);
+    }
+    if (isTauriStorageUnavailable(error)) throw error;
     return null;
   }
 
@@ -300,6 +255,59 @@ export async function saveTauriSession(session: TauriSession): Promise<void> {
   ApiClient.setAuthToken(session.token);
 }
 
+export interface TauriSessionInfo {
+  user_id: string;
+  email: string | null;
+  name: string | null;
+  issuer: string | null;
+  audience: string[];
+  issued_at: number | null;
+  expires_at: number | null;
+  /** Seconds until expiry on the server's clock (negative once expired) */
+  expires_in: number | null;
+  expired: boolean;
+  /** Local clock offset from the server in seconds, positive when ahead */
+  clock_skew: number | null;
+}
+
+/**
+ * Claims of the stored JWT (not verified), with time-to-expiry and clock skew
+ */
+export async function getTauriSessionInfo(): Promise<TauriSessionInfo | null> {
+  if (typeof window === "undefined" || !isTauri()) return null;
+  return await invoke<TauriSessionInfo | null>("get_session_info");
+}
+
+export interface TauriAccount {
+  id: string;
+  email: string;
+  name: string | null;
+  active: boolean;
+}
+
+/**
+ * Accounts signed in on this machine (the active one is flagged)
+ */
+export async function listTauriAccounts(): Promise<TauriAccount[]> {
+  if (typeof window === "undefined" || !isTauri()) return [];
+  return await invoke<TauriAccount[]>("list_accounts");
+}
+
+/**
+ * Switch to another signed-in account without logging out
+ */
+export async function switchTauriAccount(accountId: string): Promise<TauriSession | null> {
+  if (typeof window === "undefined" || !isTauri()) return null;
+
+  const stored = await invoke<StoredAuthToken>("switch_account", { accountId });
+  const user = decodeUserFromJwt(stored.token);
+  const expiresAt = stored.expires_at ?? (decodeJwtPayload(stored.token)?.exp as number | undefined);
+  if (!user || !expiresAt) return null;
+
+  ApiClient.setAuthToken(stored.token);
+  return { user, token: stored.token, expiresAt };
+}
+
 /**
  * Clear Tauri session (logout)
  */
@@ -314,7 +322,7 @@ export async function clearTauriSession(): Promise<void> {
  * Listen for OAuth callback (deep link)
  */
 export function listenForOAuthCallback(
-  onCode: (code: string, state?: string) => void,
+  onCode: (code: string, state: string) => void,
   onError: (error: string) => void
 ): () => void {
   if (!isTauri()) {
@@ -332,6 +340,10 @@ export function listenForOAuthCallback(
 
   (async () => {
     unlistenCode = await listen(TauriEvents.OAUTH_CODE_RECEIVED, (payload: OAuthCodeReceivedPayload) => {
+      if (!payload.state) {
+        onError("Missing OAuth state");
+        return;
+      }
       onCode(payload.code, payload.state);
     });
     if (!active && unlistenCode) {
@@ -6,30 +6,54 @@
 import { isTauri } from "@/lib/platform";
 import { invoke } from "@tauri-apps/api/core";
 
+export interface CalendarSyncResult {
+  connection_id: string;
+  connection_name: string;
+  status: "success" | "error";
+  error: string | null;
+  synced_at: string;
+  last_success: string | null;
+}
+
 export interface SyncStatus {
+  is_running: boolean;
   is_syncing: boolean;
+  is_online: boolean;
   last_sync: string | null;
   error: string | null;
+  calendars: CalendarSyncResult[];
+  retry_count: number;
+  next_attempt: string | null;
+}
+
+export interface SyncAttempt {
+  started_at: string;
+  duration_ms: number;
+  outcome: "success" | "failed";
+  error: string | null;
+  calendars: CalendarSyncResult[];
+}
+
+export interface SyncConfig {
+  enabled: boolean;
+  interval_minutes: number;
+  lookbehind_days: number;
+  lookahead_days: number;
 }
 
 /**
  * Start the background calendar sync service
- * This will sync every 15 minutes automatically
+ * This will sync every 15 minutes (see SyncConfig) automatically, using the session token
+ * stored on the Rust side (paused while logged out)
  */
-export async function startCalendarSyncService(
-  apiUrl: string,
-  authToken: string
-): Promise<void> {
+export async function startCalendarSyncService(apiUrl: string): Promise<void> {
   if (!isTauri()) {
     console.warn("Calendar sync service only available in Tauri");
     return;
   }
 
   try {
-    await invoke("start_sync_service", {
-      apiUrl,
-      authToken,
-    });
+    await invoke("start_sync_service", { apiUrl });
     console.log("Calendar sync service started");
   } catch (error) {
     console.error("Failed to start calendar sync service:", error);
@@ -37,6 +61,35 @@ export async function startCalendarSyncService(
   }
 }
 
+/**
+ * Stop the background calendar sync service
+ */
+export async function stopCalendarSyncService(): Promise<void> {
+  if (!isTauri()) return;
+
+  try {
+    await invoke("stop_sync_service");
+  } catch (error) {
+    console.error("Failed to stop calendar sync service:", error);
+    throw error;
+  }
+}
+
+/**
+ * Restart the background calendar sync service
+ * Reuses the previous API URL when none is given
+ */
+export async function restartCalendarSyncService(apiUrl?: string): Promise<void> {
+  if (!isTauri()) return;
+
+  try {
+    await invoke("restart_sync_service", { apiUrl: apiUrl ?? null });
+  } catch (error) {
+    console.error("Failed to restart calendar sync service:", error);
+    throw error;
+  }
+}
+
 /**
  * Manually trigger a calendar sync
  */
@@ -61,10 +114,7 @@ export async function triggerCalendarSync(
   }
 
   try {
-    await invoke("trigger_calendar_sync", {
-      apiUrl,
-      authToken,
-    });
+    await invoke("trigger_calendar_sync", { apiUrl });
   } catch (error) {
     console.error("Failed to trigger calendar sync:", error);
     throw error;
@@ -77,9 +127,14 @@ export async function triggerCalendarSync(
 export async function getSyncStatus(): Promise<SyncStatus> {
   if (!isTauri()) {
     return {
+      is_running: false,
       is_syncing: false,
+      is_online: true,
       last_sync: null,
       error: null,
+      calendars: [],
+      retry_count: 0,
+      next_attempt: null,
     };
   }
 
@@ -90,3 +145,47 @@ export async function getSyncStatus(): Promise<SyncStatus> {
     throw error;
   }
 }
+
+/**
+ * Get the calendar sync configuration
+ */
+export async function getSyncConfig(): Promise<SyncConfig | null> {
+  if (!isTauri()) return null;
+
+  try {
+    return await invoke("get_sync_config");
+  } catch (error) {
+    console.error("Failed to get sync config:", error);
+    throw error;
+  }
+}
+
+/**
+ * Update the calendar sync configuration
+ * Persisted by the Rust side and applied to the running sync loop
+ */
+export async function setSyncConfig(config: SyncConfig): Promise<void> {
+  if (!isTauri()) return;
+
+  try {
+    await invoke("set_sync_config", { config });
+  } catch (error) {
+    console.error("Failed to update sync config:", error);
+    throw error;
+  }
+}
+
+/**
+ * Get the most recent calendar sync attempts, newest first
+ * Persisted across restarts by the Rust side
+ */
+export async function getSyncHistory(limit?: number): Promise<SyncAttempt[]> {
+  if (!isTauri()) return [];
+
+  try {
+    return await invoke("get_sync_history", { limit: limit ?? null });
+  } catch (error) {
+    console.error("Failed to get sync history:", error);
+    throw error;
+  }
+}
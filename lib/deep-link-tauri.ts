@@ -0,0 +1,35 @@
+/**
+ * Tauri deep link utilities
+ * miniorg:// links are routed in Rust; the navigation they trigger is queued while
+ * the dashboard isn't listening (e.g. the link that launched the app, or on /login)
+ */
+
+import { isTauri } from "@/lib/platform";
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Tell Rust the dashboard listens for navigate-to / deep-link-error,
+ * emitting the queued events
+ */
+export async function notifyDeepLinkReady(): Promise<void> {
+  if (!isTauri()) return;
+
+  try {
+    await invoke("deep_link_ready");
+  } catch (error) {
+    console.error("Failed to replay queued deep links:", error);
+  }
+}
+
+/**
+ * Tell Rust the dashboard stopped listening, so deep link events are queued again
+ */
+export async function notifyDeepLinkClosed(): Promise<void> {
+  if (!isTauri()) return;
+
+  try {
+    await invoke("deep_link_closed");
+  } catch (error) {
+    console.error("Failed to pause deep links:", error);
+  }
+}
@@ -5,6 +5,7 @@
 
 import { emit as tauriEmit, listen as tauriListen, type UnlistenFn } from "@tauri-apps/api/event";
 import { isTauri } from "@/lib/platform";
+import type { SyncStatus } from "@/lib/calendar-sync-tauri";
 
 // Event names as typed constants
 export const TauriEvents = {
@@ -15,9 +16,19 @@ export const TauriEvents = {
   NAVIGATE_TO: "navigate-to",
   CREATE_TASK: "create-task",
   TASK_CREATED: "task-created",
+  AUTH_TOKEN_REFRESHED: "auth-token-refreshed",
+  AUTH_STATE_CHANGED: "auth-state-changed",
+  SYNC_STARTED: "sync-started",
+  SYNC_FINISHED: "sync-finished",
+  SYNC_FAILED: "sync-failed",
+  DEEP_LINK_ERROR: "deep-link-error",
 } as const;
 
 // Event payload types
+export interface OpenQuickAddPayload {
+  title: string | null;
+}
+
 export interface InvalidateQueriesPayload {
   queryKeys: string[];
 }
@@ -41,15 +52,32 @@ export interface TaskCreatedPayload {
   error?: string;
 }
 
+export interface AuthTokenRefreshedPayload {
+  token: string;
+  expires_at: number | null;
+}
+
+export interface AuthStateChangedPayload {
+  logged_in: boolean;
+  reason: "corrupt_credentials" | "logout" | "account_switched";
+  account_id: string | null;
+}
+
 // Type mapping for events
 export interface TauriEventPayloads {
-  [TauriEvents.OPEN_QUICK_ADD]: void;
+  [TauriEvents.OPEN_QUICK_ADD]: OpenQuickAddPayload | null;
   [TauriEvents.OAUTH_CODE_RECEIVED]: OAuthCodeReceivedPayload;
   [TauriEvents.OAUTH_ERROR]: string;
   [TauriEvents.INVALIDATE_QUERIES]: InvalidateQueriesPayload;
   [TauriEvents.NAVIGATE_TO]: string;
+  [TauriEvents.DEEP_LINK_ERROR]: string;
   [TauriEvents.CREATE_TASK]: CreateTaskPayload;
   [TauriEvents.TASK_CREATED]: TaskCreatedPayload;
+  [TauriEvents.AUTH_TOKEN_REFRESHED]: AuthTokenRefreshedPayload;
+  [TauriEvents.AUTH_STATE_CHANGED]: AuthStateChangedPayload;
+  [TauriEvents.SYNC_STARTED]: SyncStatus;
+  [TauriEvents.SYNC_FINISHED]: SyncStatus;
+  [TauriEvents.SYNC_FAILED]: SyncStatus;
 }
 
 /**
@@ -3,10 +3,11 @@
 import { createContext, useContext, useState, ReactNode, useCallback } from "react";
 
 type QuickAddTaskContextType = {
-  openQuickAdd: (date?: Date) => void;
+  openQuickAdd: (date?: Date, title?: string) => void;
   closeQuickAdd: () => void;
   isOpen: boolean;
   prefilledDate?: Date;
+  prefilledTitle?: string;
 };
 
 const QuickAddTaskContext = createContext<QuickAddTaskContextType | undefined>(undefined);
@@ -14,19 +15,22 @@ const QuickAddTaskContext = createContext<QuickAddTaskContextType | undefined>(u
 export function QuickAddTaskProvider({ children }: { children: ReactNode }) {
   const [isOpen, setIsOpen] = useState(false);
   const [prefilledDate, setPrefilledDate] = useState<Date | undefined>();
+  const [prefilledTitle, setPrefilledTitle] = useState<string | undefined>();
 
-  const openQuickAdd = useCallback((date?: Date) => {
+  const openQuickAdd = useCallback((date?: Date, title?: string) => {
     setPrefilledDate(date);
+    setPrefilledTitle(title);
     setIsOpen(true);
   }, []);
 
   const closeQuickAdd = useCallback(() => {
     setIsOpen(false);
     setPrefilledDate(undefined);
+    setPrefilledTitle(undefined);
   }, []);
 
   return (
-    <QuickAddTaskContext.Provider value={{ openQuickAdd, closeQuickAdd, isOpen, prefilledDate }}>
+    <QuickAddTaskContext.Provider value={{ openQuickAdd, closeQuickAdd, isOpen, prefilledDate, prefilledTitle }}>
       {children}
     </QuickAddTaskContext.Provider>
   );
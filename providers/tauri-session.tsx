@@ -9,7 +9,9 @@ import React, {
 } from "react";
 import { useSession as useNextAuthSession } from "next-auth/react";
 import { useRouter } from "next/navigation";
+import { ApiClient } from "@/lib/api/client";
 import { isTauri } from "@/lib/platform";
+import { listen, TauriEvents, type AuthStateChangedPayload } from "@/lib/tauri/events";
 import {
   getTauriSession,
   saveTauriSession,
@@ -17,6 +19,8 @@ import {
   startTauriOAuthFlow,
   exchangeCodeForToken,
   listenForOAuthCallback,
+  startTauriTokenRefresh,
+  isTauriStorageUnavailable,
   type TauriSession,
 } from "@/lib/auth-tauri";
 import { clearOnboardingState } from "@/lib/hooks/use-onboarding";
@@ -53,6 +57,26 @@ const TauriSessionContext = createContext<TauriSessionContextType | undefined>(
   undefined
 );
 
+const STORAGE_ERROR_TOAST_ID = "tauri-session-storage";
+
+/**
+ * The keychain is locked or failing: the session is unknown, not gone.
+ * Tell the user and let them retry once it is unlocked.
+ */
+function showStorageError(error: unknown, retry: () => void) {
+  const locked = isTauriStorageUnavailable(error) && error.kind === "locked";
+  toast.error(locked ? "Keychain locked" : "Could not read your saved session", {
+    id: STORAGE_ERROR_TOAST_ID,
+    description: locked
+      ? "Unlock your keychain, then retry"
+      : isTauriStorageUnavailable(error)
+        ? error.message
+        : undefined,
+    duration: Infinity,
+    action: { label: "Retry", onClick: retry },
+  });
+}
+
 /**
  * Unified session provider that works for both web (NextAuth) and Tauri (JWT)
  */
@@ -104,28 +128,113 @@ export function TauriSessionProvider({
 
     let active = true;
 
-    (async () => {
-      const session = await getTauriSession();
+    const load = async () => {
+      let session: TauriSession | null;
+      try {
+        session = await getTauriSession();
+      } catch (error) {
+        // Stays "loading": a locked keychain is not a logout
+        if (active) showStorageError(error, load);
+        return;
+      }
       if (shouldLog) {
         console.log(LOG_PREFIX, "load session from storage", session);
       }
       if (!active) return;
+      toast.dismiss(STORAGE_ERROR_TOAST_ID);
       if (session) {
         setTauriStatus("authenticated");
         setTauriSession(session);
       } else {
         setTauriStatus("unauthenticated");
       }
-    })();
+    };
+
+    load();
+
+    return () => {
+      active = false;
+    };
+  }, [isDesktop, shouldLog]);
+
+  // Keep the JWT fresh - the Rust token manager refreshes it before expiry
+  useEffect(() => {
+    if (!isDesktop) return;
+
+    let cleanup: (() => void) | null = null;
+    let active = true;
+
+    startTauriTokenRefresh()
+      .then((unlisten) => {
+        if (active) {
+          cleanup = unlisten;
+        } else {
+          unlisten();
+        }
+      })
+      .catch((error) => {
+        console.error("Failed to start token refresh:", error);
+      });
+
+    return () => {
+      active = false;
+      if (cleanup) cleanup();
+    };
+  }, [isDesktop]);
+
+  // Rust reports sessions that ended (logout in any window, a corrupt stored token) - reload from storage
+  useEffect(() => {
+    if (!isDesktop) return;
+
+    let cleanup: (() => void) | null = null;
+    let active = true;
+
+    const onAuthStateChanged = async (payload: AuthStateChangedPayload) => {
+      if (shouldLog) {
+        console.log(LOG_PREFIX, "auth state changed", payload);
+      }
+      let session: TauriSession | null;
+      try {
+        session = payload.logged_in ? await getTauriSession() : null;
+      } catch (error) {
+        // Keep the current state until the keychain can be read
+        if (active) showStorageError(error, () => onAuthStateChanged(payload));
+        return;
+      }
+      if (!active) return;
+      toast.dismiss(STORAGE_ERROR_TOAST_ID);
+      if (!session) {
+        // Don't keep the previous user's bearer or data in this window
+        ApiClient.clearAuthToken();
+        clearQueryCache();
+      } else if (payload.reason === "account_switched") {
+        // getTauriSession() set the new bearer; drop the other account's data
+        clearQueryCache();
+      }
+      setTauriSession(session);
+      setTauriStatus(session ? "authenticated" : "unauthenticated");
+      if (payload.reason === "corrupt_credentials" && !session) {
+        toast.error("Your saved session could not be read, please log in again");
+      }
+    };
+
+    listen(TauriEvents.AUTH_STATE_CHANGED, onAuthStateChanged).then((unlisten) => {
+      if (active) {
+        cleanup = unlisten;
+      } else {
+        unlisten();
+      }
+    });
 
     return () => {
       active = false;
+      if (cleanup) cleanup();
     };
   }, [isDesktop, shouldLog]);
 
   // Listen for OAuth callbacks (Tauri only, main window only)
-  // The quick-add window should NOT process OAuth callbacks since it doesn't have
-  // the redirect URI and code verifier that were set when the flow started.
+  // The quick-add window should NOT process OAuth callbacks - the code is
+  // exchanged once, by the window that started the flow.
   useEffect(() => {
     if (!isDesktop) return;
 
@@ -160,7 +269,7 @@ export function TauriSessionProvider({
               console.log(LOG_PREFIX, "oauth callback received", { code });
             }
             setTauriStatus("loading");
-            const session = await exchangeCodeForToken(code, undefined, state);
+            const session = await exchangeCodeForToken(code, state);
             if (shouldLog) {
               console.log(LOG_PREFIX, "token exchange success", session);
             }
@@ -177,8 +286,17 @@ export function TauriSessionProvider({
           }
         },
         (error) => {
+          // A stray callback (unknown or expired state): the login in progress goes on
+          if (error === "invalid_state") {
+            console.warn("Ignoring OAuth callback with an invalid state");
+            return;
+          }
           console.error("OAuth error:", error);
-          toast.error("Authentication error: " + error);
+          if (error === "timeout") {
+            toast.error("Login timed out, please try again");
+          } else if (error !== "cancelled") {
+            toast.error("Authentication error: " + error);
+          }
           setTauriStatus("unauthenticated");
         }
       );
@@ -1,26 +1,182 @@
 use serde::{Deserialize, Serialize};
-use keyring::Entry;
-use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, Listener};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::calendar_sync::SyncService;
+use crate::credential_store::{CredentialStore, EncryptedFileStore, KeyringStore};
+use crate::loopback::{read_request, write_response, CallbackParams};
+use crate::oauth_page::{self, PageLocale};
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpListener,
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, oneshot, watch, Mutex as AsyncMutex},
 };
 use url::Url;
 
-// Global state to store the OAuth code
+/// Error of the auth commands, sent to the frontend as `{ "kind": "locked", "message": "..." }`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum AuthError {
+    /// No such stored entry, account or OAuth flow
+    NotFound(String),
+    /// The credential store is locked or can't be reached right now (e.g. a locked keychain)
+    Locked(String),
+    /// Access was refused: file permissions, an expired OAuth flow, a 401/403 from the API
+    Denied(String),
+    /// Stored data or a server response could not be decoded
+    Corrupt(String),
+    /// Any other failure of the storage backend, the network or the OS
+    Backend(String),
+}
+
+impl AuthError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::Locked(message)
+            | Self::Denied(message)
+            | Self::Corrupt(message)
+            | Self::Backend(message) => message,
+        }
+    }
+
+    /// Classify an unsuccessful API response
+    fn from_response(context: &str, status: reqwest::StatusCode, body: &str) -> Self {
+        let message = format!("{}: {}", context, body);
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                Self::Denied(message)
+            }
+            _ => Self::Backend(message),
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+// Pending OAuth flows, keyed by the server-signed `state` sent to the provider
+#[derive(Default)]
 struct OAuthState {
-    code: Mutex<Option<String>>,
-    error: Mutex<Option<String>>,
+    pending: Mutex<HashMap<String, PendingFlow>>,
 }
 
-impl Default for OAuthState {
-    fn default() -> Self {
-        Self {
-            code: Mutex::new(None),
-            error: Mutex::new(None),
+struct PendingFlow {
+    code_verifier: String,
+    redirect_uri: String,
+    created_at: i64,
+    /// Set once a callback carrying this state was accepted
+    callback_received: bool,
+}
+
+impl PendingFlow {
+    fn is_expired(&self, now: i64) -> bool {
+        now - self.created_at > OAUTH_FLOW_TTL_SECS
+    }
+}
+
+impl OAuthState {
+    /// Register a new flow under `state` with a fresh PKCE verifier.
+    /// Returns the S256 code challenge.
+    fn begin(&self, state: String, redirect_uri: String) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, flow| !flow.is_expired(now));
+
+        let code_verifier = random_token();
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        pending.insert(
+            state,
+            PendingFlow {
+                code_verifier,
+                redirect_uri,
+                created_at: now,
+                callback_received: false,
+            },
+        );
+
+        code_challenge
+    }
+
+    fn is_pending(&self, state: &str) -> bool {
+        let now = chrono::Utc::now().timestamp();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(state)
+            .is_some_and(|flow| !flow.is_expired(now) && !flow.callback_received)
+    }
+
+    /// Accept a callback only if its state belongs to a pending flow.
+    /// A repeated callback for the same flow is reported as a duplicate.
+    fn verify_callback(&self, state: Option<&str>) -> Result<CallbackMatch, String> {
+        let state = state.ok_or_else(|| "Missing OAuth state".to_string())?;
+        let now = chrono::Utc::now().timestamp();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        let flow = pending
+            .get_mut(state)
+            .ok_or_else(|| "Invalid OAuth state".to_string())?;
+        if flow.is_expired(now) {
+            pending.remove(state);
+            return Err("OAuth flow expired".to_string());
+        }
+        if flow.callback_received {
+            return Ok(CallbackMatch::Duplicate);
         }
+
+        flow.callback_received = true;
+        Ok(CallbackMatch::Accepted)
     }
+
+    /// Forget every pending flow (on logout)
+    fn clear(&self) {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Remove a flow whose callback was received, for the code exchange
+    fn take_for_exchange(&self, state: &str) -> Result<PendingFlow, AuthError> {
+        let now = chrono::Utc::now().timestamp();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        match pending.remove(state) {
+            Some(flow) if flow.is_expired(now) => {
+                Err(AuthError::Denied("OAuth flow expired".to_string()))
+            }
+            Some(flow) if !flow.callback_received => {
+                // Not ours to exchange yet, keep waiting for the callback
+                pending.insert(state.to_string(), flow);
+                Err(AuthError::Denied("OAuth callback not received".to_string()))
+            }
+            Some(flow) => Ok(flow),
+            None => Err(AuthError::NotFound("Invalid OAuth state".to_string())),
+        }
+    }
+}
+
+/// Result of matching a callback against the pending flows
+#[derive(Debug, PartialEq)]
+enum CallbackMatch {
+    Accepted,
+    /// The flow already received its callback (e.g. the same deep link delivered twice)
+    Duplicate,
+}
+
+/// 32 random bytes, base64url-encoded (valid as a PKCE verifier)
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,311 +185,1232 @@ pub struct AuthToken {
     pub expires_at: Option<i64>,
 }
 
+impl AuthToken {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|exp| exp <= chrono::Utc::now().timestamp())
+            .unwrap_or(false)
+    }
+}
+
+/// A MiniOrg account with a stored token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub id: String,
+    pub email: String,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSummary {
+    #[serde(flatten)]
+    pub account: Account,
+    pub active: bool,
+}
+
+/// On-disk form of a stored token. Entries written before versioning are the bare
+/// `{token, expires_at}` and read as version 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TokenEnvelope {
+    #[serde(default = "legacy_token_version")]
+    version: u32,
+    #[serde(flatten)]
+    token: AuthToken,
+    stored_at: Option<i64>,
+}
+
+fn legacy_token_version() -> u32 {
+    1
+}
+
+/// What the active session's JWT says, for "signed in as" and expiry warnings
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub issuer: Option<String>,
+    pub audience: Vec<String>,
+    pub issued_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    /// Seconds until the server considers the token expired (negative once it has)
+    pub expires_in: Option<i64>,
+    pub expired: bool,
+    /// Estimated offset of the local clock from the server's, in seconds
+    /// (positive when the local clock is ahead). Unknown for tokens stored by older builds.
+    pub clock_skew: Option<i64>,
+}
+
+/// Payload of `auth-state-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthStateChanged {
+    /// Whether an account is still active afterwards
+    pub logged_in: bool,
+    pub reason: AuthStateReason,
+    pub account_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthStateReason {
+    /// A stored token or the account list could not be read and was quarantined
+    CorruptCredentials,
+    /// The user logged out of the active account
+    Logout,
+    /// Another stored account became the active one
+    AccountSwitched,
+}
+
+/// Accounts signed in on this machine and which one the app uses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AccountIndex {
+    active: Option<String>,
+    accounts: Vec<Account>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct OAuthCallbackPayload {
     pub code: String,
     pub state: Option<String>,
 }
 
-// Store for persistent auth token
-static AUTH_TOKEN: Mutex<Option<AuthToken>> = Mutex::new(None);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthUser {
+    pub id: String,
+    pub email: String,
+    pub name: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Session returned by the token exchange endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthSession {
+    pub token: String,
+    pub expires_at: i64,
+    pub user: OAuthUser,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerStateResponse {
+    state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshResponse {
+    token: String,
+    expires_at: Option<i64>,
+}
+
+/// The credential backend picked at startup
+struct SelectedStore {
+    store: Box<dyn CredentialStore>,
+    fallback_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CredentialStoreStatus {
+    pub backend: &'static str,
+    /// Why the OS keyring could not be used, when falling back to the encrypted file
+    pub fallback_reason: Option<String>,
+}
+
+static CREDENTIAL_STORE: OnceLock<SelectedStore> = OnceLock::new();
+
+lazy_static::lazy_static! {
+    static ref OAUTH_STATE: OAuthState = OAuthState::default();
+}
+
+/// The session token, kept in Tauri state (`app.manage`)
+pub struct AuthStore {
+    /// Held while the credential store is read or written, so the first load is
+    /// done once however many callers wait for it, and writes don't interleave
+    token: AsyncMutex<CachedToken>,
+    /// Broadcasts the current token to background tasks whenever it changes
+    watch: watch::Sender<Option<AuthToken>>,
+    /// Emits the `auth-state-changed` events reported by the storage helpers
+    app: AppHandle,
+}
+
+enum CachedToken {
+    /// Not read from the credential store yet (or the last read failed)
+    Unloaded,
+    Loaded(Option<AuthToken>),
+}
+
+impl AuthStore {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            token: AsyncMutex::new(CachedToken::Unloaded),
+            watch: watch::channel(None).0,
+            app,
+        }
+    }
+
+    /// Token of the active account, read from the credential store on first use
+    async fn token(&self) -> Result<Option<AuthToken>, AuthError> {
+        let mut cached = self.token.lock().await;
+        if let CachedToken::Loaded(token) = &*cached {
+            return Ok(token.clone());
+        }
+
+        let stored = self.with_credentials(read_stored_token).await?;
+        *cached = CachedToken::Loaded(stored.clone());
+        if stored.is_some() {
+            self.watch.send_replace(stored.clone());
+        }
+        Ok(stored)
+    }
+
+    fn publish(&self, cached: &mut CachedToken, token: Option<AuthToken>) {
+        *cached = CachedToken::Loaded(token.clone());
+        self.watch.send_replace(token);
+    }
+
+    /// Run credential store work with `run_blocking`, then emit the
+    /// `auth-state-changed` events it reported, even if it failed afterwards
+    async fn with_credentials<T, F>(&self, work: F) -> Result<T, AuthError>
+    where
+        F: FnOnce(&mut Vec<AuthStateChanged>) -> Result<T, AuthError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (result, changes) = run_blocking(move || {
+            let mut changes = Vec::new();
+            let result = work(&mut changes);
+            Ok((result, changes))
+        })
+        .await?;
+        for change in changes {
+            let _ = self.app.emit("auth-state-changed", change);
+        }
+        result
+    }
+}
+
+/// Run credential store work on the blocking pool: keyring calls can wait on OS
+/// prompts, and a panic there fails this call instead of poisoning shared state
+async fn run_blocking<T, F>(work: F) -> Result<T, AuthError>
+where
+    F: FnOnce() -> Result<T, AuthError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| AuthError::Backend(format!("Credential store task failed: {}", e)))?
+}
+
+// Handle of the running token manager (at most one per app)
+static TOKEN_MANAGER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+// The OAuth loopback listener waiting for a callback, if any
+static OAUTH_LISTENER: Mutex<Option<LoopbackListener>> = Mutex::new(None);
+
+/// How long a started OAuth flow waits for its callback
+const OAUTH_FLOW_TTL_SECS: i64 = 10 * 60;
+/// Default time the loopback listener waits for the callback
+const OAUTH_LISTENER_TIMEOUT_SECS: u64 = 5 * 60;
+/// Give up on a loopback connection that sends nothing for this long
+const LOOPBACK_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const GOOGLE_SCOPES: &[&str] = &[
+    "openid",
+    "email",
+    "profile",
+    "https://www.googleapis.com/auth/calendar.readonly",
+    "https://www.googleapis.com/auth/calendar.events",
+];
 
 const KEYRING_SERVICE: &str = "miniorg";
+/// Prefix of per-account token entries (and the single entry used before multi-account)
 const KEYRING_USER: &str = "auth_token";
+/// Entry holding the `AccountIndex`
+const KEYRING_ACCOUNTS: &str = "accounts";
+/// Prefix of the entries corrupt values are moved to (one slot per original key)
+const KEYRING_QUARANTINE: &str = "quarantine";
+/// Version of `TokenEnvelope` written by this build
+const TOKEN_ENVELOPE_VERSION: u32 = 2;
+
+/// Refresh the token this long before it expires (tokens are valid for 7 days)
+const REFRESH_LEEWAY_SECS: i64 = 24 * 60 * 60;
+/// Delay before retrying a failed refresh
+const REFRESH_RETRY_SECS: u64 = 5 * 60;
+/// Upper bound on a single wait, so wall-clock jumps (sleep/resume) are noticed
+const REFRESH_CHECK_SECS: i64 = 15 * 60;
 
-fn keyring_entry() -> Result<Entry, String> {
-    Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| e.to_string())
+/// Choose where credentials are kept: the OS keyring when it works, otherwise an
+/// encrypted file in the app data dir. Call once at startup, before any token access.
+pub fn init_credential_store(app_data_dir: &Path) {
+    let keyring = KeyringStore::new(KEYRING_SERVICE);
+    let selected = match keyring.probe() {
+        Ok(()) => SelectedStore {
+            store: Box::new(keyring),
+            fallback_reason: None,
+        },
+        Err(e) => {
+            eprintln!(
+                "OS keyring unavailable ({}), storing credentials in an encrypted file",
+                e
+            );
+            SelectedStore {
+                store: Box::new(EncryptedFileStore::new(app_data_dir)),
+                fallback_reason: Some(e.to_string()),
+            }
+        }
+    };
+    if CREDENTIAL_STORE.set(selected).is_err() {
+        eprintln!("Credential store already initialized");
+    }
+}
+
+fn selected_store() -> &'static SelectedStore {
+    CREDENTIAL_STORE.get_or_init(|| SelectedStore {
+        store: Box::new(KeyringStore::new(KEYRING_SERVICE)),
+        fallback_reason: None,
+    })
 }
 
-fn read_token_from_keyring() -> Result<Option<AuthToken>, String> {
-    let entry = keyring_entry()?;
-    match entry.get_password() {
-        Ok(value) => {
-            let token = serde_json::from_str::<AuthToken>(&value)
-                .map_err(|e| format!("Failed to parse stored token: {}", e))?;
-            Ok(Some(token))
+fn credential_store() -> &'static dyn CredentialStore {
+    selected_store().store.as_ref()
+}
+
+/// Which credential backend is in use, and why the keyring was not
+#[tauri::command]
+pub fn get_credential_store_status() -> CredentialStoreStatus {
+    let selected = selected_store();
+    CredentialStoreStatus {
+        backend: selected.store.name(),
+        fallback_reason: selected.fallback_reason.clone(),
+    }
+}
+
+fn read_credential(user: &str) -> Result<Option<String>, AuthError> {
+    credential_store().get(user)
+}
+
+fn write_credential(user: &str, value: &str) -> Result<(), AuthError> {
+    credential_store().set(user, value)
+}
+
+fn delete_credential(user: &str) -> Result<(), AuthError> {
+    credential_store().delete(user)
+}
+
+/// Credential key holding the token of one account
+fn token_key(account_id: &str) -> String {
+    format!("{}:{}", KEYRING_USER, account_id)
+}
+
+/// Claims of a MiniOrg JWT that the app reads
+#[derive(Debug, Deserialize)]
+struct TokenClaims {
+    sub: String,
+    email: Option<String>,
+    name: Option<String>,
+    iss: Option<String>,
+    aud: Option<Audience>,
+    iat: Option<i64>,
+    exp: Option<i64>,
+}
+
+/// `aud` is either a single string or a list
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Claims of a token, decoded but not verified - the server does that
+fn decode_claims(token: &str) -> Result<TokenClaims, AuthError> {
+    let payload = token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| AuthError::Corrupt("Malformed token".to_string()))?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|e| AuthError::Corrupt(format!("Malformed token: {}", e)))?;
+    serde_json::from_slice::<TokenClaims>(&bytes)
+        .map_err(|e| AuthError::Corrupt(format!("Malformed token claims: {}", e)))
+}
+
+/// Account a token belongs to, read from its JWT claims
+fn account_from_token(token: &str) -> Result<Account, AuthError> {
+    let claims = decode_claims(token)?;
+    Ok(Account {
+        id: claims.sub,
+        email: claims.email.unwrap_or_default(),
+        name: claims.name,
+    })
+}
+
+/// Serialize a token as the current `TokenEnvelope`.
+/// `stored_at` is when the token was received (unknown for migrated entries).
+fn encode_token(token: &AuthToken, stored_at: Option<i64>) -> Result<String, AuthError> {
+    let envelope = TokenEnvelope {
+        version: TOKEN_ENVELOPE_VERSION,
+        token: token.clone(),
+        stored_at,
+    };
+    serde_json::to_string(&envelope).map_err(|e| AuthError::Backend(e.to_string()))
+}
+
+/// Parse a stored token of any known version
+fn decode_token(value: &str) -> Result<TokenEnvelope, AuthError> {
+    let envelope = serde_json::from_str::<TokenEnvelope>(value)
+        .map_err(|e| AuthError::Corrupt(format!("Failed to parse stored token: {}", e)))?;
+    if envelope.version > TOKEN_ENVELOPE_VERSION {
+        return Err(AuthError::Corrupt(format!(
+            "Unsupported stored token version {}",
+            envelope.version
+        )));
+    }
+    if envelope.token.token.is_empty() {
+        return Err(AuthError::Corrupt("Stored token is empty".to_string()));
+    }
+    Ok(envelope)
+}
+
+/// Move an unreadable value out of the way so it stops failing every launch,
+/// keeping it under `quarantine:<key>` for inspection. A value the backend could not
+/// even return as text (e.g. a keyring entry that isn't UTF-8) is only deleted.
+fn quarantine_credential(
+    key: &str,
+    value: Option<&str>,
+    reason: &AuthError,
+) -> Result<(), AuthError> {
+    eprintln!("Quarantining corrupt credential {}: {}", key, reason);
+    if let Some(value) = value {
+        write_credential(&format!("{}:{}", KEYRING_QUARANTINE, key), value)?;
+    }
+    delete_credential(key)
+}
+
+/// A credential read with `read_parsed_credential`
+enum StoredCredential<T> {
+    Missing,
+    Parsed(T),
+    /// The value was corrupt and has been quarantined
+    Quarantined,
+}
+
+/// Read a credential and parse it. A corrupt value, whether the backend fails to
+/// return it or `parse` rejects it, is quarantined; other errors are returned.
+fn read_parsed_credential<T>(
+    key: &str,
+    parse: impl FnOnce(&str) -> Result<T, AuthError>,
+) -> Result<StoredCredential<T>, AuthError> {
+    let (value, reason) = match read_credential(key) {
+        Ok(None) => return Ok(StoredCredential::Missing),
+        Ok(Some(value)) => match parse(&value) {
+            Ok(parsed) => return Ok(StoredCredential::Parsed(parsed)),
+            Err(e @ AuthError::Corrupt(_)) => (Some(value), e),
+            Err(e) => return Err(e),
+        },
+        Err(e @ AuthError::Corrupt(_)) => (None, e),
+        Err(e) => return Err(e),
+    };
+    quarantine_credential(key, value.as_deref(), &reason)?;
+    Ok(StoredCredential::Quarantined)
+}
+
+/// Load the account index, moving a token stored by older versions
+/// under the single `auth_token` entry to its account.
+/// A corrupt index or legacy entry is quarantined and reads as no accounts.
+///
+/// The storage helpers push an `auth-state-changed` event to `changes` for each
+/// account they sign out, which `AuthStore::with_credentials` emits.
+fn read_account_index(changes: &mut Vec<AuthStateChanged>) -> Result<AccountIndex, AuthError> {
+    let mut signed_out = || {
+        changes.push(AuthStateChanged {
+            logged_in: false,
+            reason: AuthStateReason::CorruptCredentials,
+            account_id: None,
+        });
+        Ok(AccountIndex::default())
+    };
+
+    let stored = read_parsed_credential(KEYRING_ACCOUNTS, |value| {
+        serde_json::from_str::<AccountIndex>(value)
+            .map_err(|e| AuthError::Corrupt(format!("Failed to parse stored accounts: {}", e)))
+    })?;
+    match stored {
+        StoredCredential::Parsed(index) => return Ok(index),
+        StoredCredential::Quarantined => return signed_out(),
+        StoredCredential::Missing => {}
+    }
+
+    let legacy = read_parsed_credential(KEYRING_USER, |value| {
+        let envelope = decode_token(value)?;
+        let account = account_from_token(&envelope.token.token)?;
+        Ok((envelope.token, account))
+    })?;
+    let mut index = AccountIndex::default();
+    match legacy {
+        StoredCredential::Parsed((token, account)) => {
+            write_credential(&token_key(&account.id), &encode_token(&token, None)?)?;
+            index.active = Some(account.id.clone());
+            index.accounts.push(account);
+            write_account_index(&index)?;
+            delete_credential(KEYRING_USER)?;
         }
-        Err(_) => Ok(None),
+        StoredCredential::Quarantined => return signed_out(),
+        StoredCredential::Missing => {}
     }
+    Ok(index)
 }
 
-fn write_token_to_keyring(token: &AuthToken) -> Result<(), String> {
-    let entry = keyring_entry()?;
-    let payload = serde_json::to_string(token).map_err(|e| e.to_string())?;
-    entry.set_password(&payload).map_err(|e| e.to_string())
+fn write_account_index(index: &AccountIndex) -> Result<(), AuthError> {
+    let payload = serde_json::to_string(index).map_err(|e| AuthError::Backend(e.to_string()))?;
+    write_credential(KEYRING_ACCOUNTS, &payload)
 }
 
-fn clear_token_in_keyring() -> Result<(), String> {
-    let entry = keyring_entry()?;
-    match entry.delete_password() {
-        Ok(_) => Ok(()),
-        Err(err) => {
-            let message = err.to_string();
-            if message.to_lowercase().contains("no entry") {
-                return Ok(());
+/// Stored token of one account. Older entries are rewritten in the current format;
+/// a corrupt one is quarantined and its account signed out, which reads as `None`.
+fn read_account_envelope(
+    account_id: &str,
+    changes: &mut Vec<AuthStateChanged>,
+) -> Result<Option<TokenEnvelope>, AuthError> {
+    let key = token_key(account_id);
+    match read_parsed_credential(&key, decode_token)? {
+        StoredCredential::Parsed(envelope) => {
+            if envelope.version < TOKEN_ENVELOPE_VERSION {
+                let migrated = encode_token(&envelope.token, envelope.stored_at)
+                    .and_then(|payload| write_credential(&key, &payload));
+                if let Err(e) = migrated {
+                    eprintln!("Failed to migrate stored token of {}: {}", account_id, e);
+                }
             }
-            Err(message)
+            Ok(Some(envelope))
         }
+        StoredCredential::Quarantined => {
+            let mut index = read_account_index(changes)?;
+            index.accounts.retain(|a| a.id != account_id);
+            if index.active.as_deref() == Some(account_id) {
+                index.active = None;
+            }
+            write_account_index(&index)?;
+
+            changes.push(AuthStateChanged {
+                logged_in: index.active.is_some(),
+                reason: AuthStateReason::CorruptCredentials,
+                account_id: Some(account_id.to_string()),
+            });
+            Ok(None)
+        }
+        StoredCredential::Missing => Ok(None),
+    }
+}
+
+fn read_account_token(
+    account_id: &str,
+    changes: &mut Vec<AuthStateChanged>,
+) -> Result<Option<AuthToken>, AuthError> {
+    Ok(read_account_envelope(account_id, changes)?.map(|envelope| envelope.token))
+}
+
+/// Token of the active account
+fn read_stored_token(changes: &mut Vec<AuthStateChanged>) -> Result<Option<AuthToken>, AuthError> {
+    match read_account_index(changes)?.active {
+        Some(account_id) => read_account_token(&account_id, changes),
+        None => Ok(None),
+    }
+}
+
+/// Store a token under its account and make that account the active one
+fn write_stored_token(
+    token: &AuthToken,
+    changes: &mut Vec<AuthStateChanged>,
+) -> Result<(), AuthError> {
+    let account = account_from_token(&token.token)?;
+    let stored_at = chrono::Utc::now().timestamp();
+    write_credential(&token_key(&account.id), &encode_token(token, Some(stored_at))?)?;
+
+    let mut index = read_account_index(changes)?;
+    index.active = Some(account.id.clone());
+    match index.accounts.iter_mut().find(|a| a.id == account.id) {
+        Some(existing) => *existing = account,
+        None => index.accounts.push(account),
     }
+    write_account_index(&index)
 }
 
-/// Start OAuth flow by opening browser
+/// Sign the active account out: forget its token and remove it from the list.
+/// Returns the account that was signed out, if any.
+fn clear_stored_token(
+    changes: &mut Vec<AuthStateChanged>,
+) -> Result<Option<String>, AuthError> {
+    let mut index = read_account_index(changes)?;
+    let Some(account_id) = index.active.take() else {
+        return Ok(None);
+    };
+    delete_credential(&token_key(&account_id))?;
+    index.accounts.retain(|a| a.id != account_id);
+    write_account_index(&index)?;
+    Ok(Some(account_id))
+}
+
+/// Start OAuth flow by opening browser.
+/// `auth_url` must come from `build_oauth_url`, so its state is a pending flow and its
+/// redirect is the loopback listener (`start_oauth_listener`): Google and the token
+/// endpoint only accept loopback redirects for the desktop app, not `miniorg://`.
 #[tauri::command]
 pub async fn start_oauth_flow(
     app_handle: AppHandle,
     auth_url: String,
-) -> Result<String, String> {
+) -> Result<String, AuthError> {
     println!("Starting OAuth flow with URL: {}", auth_url);
-    
+
+    let state = Url::parse(&auth_url)
+        .map_err(|e| AuthError::Corrupt(format!("Invalid authorization URL: {}", e)))?
+        .query_pairs()
+        .find(|(key, _)| key == "state")
+        .map(|(_, value)| value.into_owned());
+    if !state.is_some_and(|state| OAUTH_STATE.is_pending(&state)) {
+        return Err(AuthError::NotFound(
+            "Authorization URL has no pending OAuth state".to_string(),
+        ));
+    }
+
     // Open the OAuth URL in the user's default browser
     use tauri_plugin_shell::ShellExt;
-    app_handle.shell().open(&auth_url, None)
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
-
-    // Register deep link protocol handler
-    // The OAuth redirect will be to tauri://localhost?code=xxx
-    let _id = app_handle.listen_any("oauth-callback", move |event: tauri::Event| {
-        let payload = event.payload();
-        println!("Received OAuth callback: {}", payload);
-        
-        // Parse the callback URL
-        if let Ok(url) = url::Url::parse(&format!("tauri://localhost{}", payload)) {
-            if let Some(code) = url.query_pairs().find(|(key, _)| key == "code") {
-                println!("OAuth code received: {}", code.1);
-                // Store the code (this would be picked up by the frontend)
-                // In practice, we'll emit an event to the frontend
-            }
-        }
-    });
+    if let Err(e) = app_handle.shell().open(&auth_url, None) {
+        return Err(AuthError::Backend(format!("Failed to open browser: {}", e)));
+    }
 
     Ok("OAuth flow started. Waiting for callback...".to_string())
 }
 
 /// Get stored auth token
 #[tauri::command]
-pub fn get_auth_token() -> Result<Option<AuthToken>, String> {
-    let token = AUTH_TOKEN.lock().unwrap();
-    if token.is_some() {
-        return Ok(token.clone());
+pub async fn get_auth_token(store: State<'_, AuthStore>) -> Result<Option<AuthToken>, AuthError> {
+    store.token().await
+}
+
+/// Current session token, or `None` when logged out or expired.
+/// Background tasks call this on every run instead of capturing a token.
+pub async fn current_token(app: &AppHandle) -> Option<AuthToken> {
+    match app.state::<AuthStore>().token().await {
+        Ok(token) => token.filter(|t| !t.is_expired()),
+        Err(e) => {
+            eprintln!("Failed to read auth token: {}", e);
+            None
+        }
     }
-    drop(token);
+}
 
-    let stored = read_token_from_keyring()?;
-    let mut token = AUTH_TOKEN.lock().unwrap();
-    *token = stored.clone();
-    Ok(stored)
+/// Subscribe to token changes (login, refresh, logout)
+pub fn subscribe_token(app: &AppHandle) -> watch::Receiver<Option<AuthToken>> {
+    app.state::<AuthStore>().watch.subscribe()
 }
 
-/// Set auth token (after successful OAuth)
+/// Set auth token (after successful OAuth), resuming calendar sync if a logout stopped it
 #[tauri::command]
-pub fn set_auth_token(token: String, expires_at: Option<i64>) -> Result<(), String> {
-    let mut auth_token = AUTH_TOKEN.lock().unwrap();
+pub async fn set_auth_token(
+    app_handle: AppHandle,
+    store: State<'_, AuthStore>,
+    token: String,
+    expires_at: Option<i64>,
+) -> Result<(), AuthError> {
+    let mut cached = store.token.lock().await;
     let session = AuthToken { token, expires_at };
-    *auth_token = Some(session.clone());
-    write_token_to_keyring(&session)?;
+    let stored = session.clone();
+    store
+        .with_credentials(move |changes| write_stored_token(&stored, changes))
+        .await?;
+    store.publish(&mut cached, Some(session));
+    drop(cached);
+
+    resume_sync(&app_handle).await;
     Ok(())
 }
 
-/// Clear auth token (logout)
+/// Start calendar sync again after a login, if `clear_auth_token` stopped it
+async fn resume_sync(app_handle: &AppHandle) {
+    if let Some(sync) = app_handle.try_state::<SyncService>() {
+        sync.resume_after_login().await;
+    }
+}
+
+/// Log out of the active account (other accounts stay signed in) and end what runs
+/// for its session: calendar sync, the quick-add panel and any pending OAuth flow.
+/// Every window gets `auth-state-changed`. The token manager stays, idle until a new login.
+#[tauri::command]
+pub async fn clear_auth_token(
+    app_handle: AppHandle,
+    store: State<'_, AuthStore>,
+) -> Result<(), AuthError> {
+    let mut cached = store.token.lock().await;
+    store.publish(&mut cached, None);
+
+    // Nothing may keep using the old bearer, even if clearing the storage fails below
+    if let Some(sync) = app_handle.try_state::<SyncService>() {
+        sync.stop_for_logout().await;
+    }
+    // AppKit panels may only be touched from the main thread, and this command is async
+    let panel_app = app_handle.clone();
+    let _ = app_handle.run_on_main_thread(move || {
+        let _ = crate::hide_quick_add_window(panel_app);
+    });
+    OAUTH_STATE.clear();
+    if let Some(listener) = OAUTH_LISTENER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+    {
+        listener.handle.abort();
+    }
+
+    let cleared = store.with_credentials(clear_stored_token).await;
+    drop(cached);
+
+    let _ = app_handle.emit(
+        "auth-state-changed",
+        AuthStateChanged {
+            logged_in: false,
+            reason: AuthStateReason::Logout,
+            account_id: cleared.as_ref().ok().and_then(|id| id.clone()),
+        },
+    );
+    cleared.map(|_| ())
+}
+
+/// Claims of the active account's token, decoded without verification.
+/// `expires_in` is corrected for clock skew, estimated from when the token was
+/// stored (local clock) versus its `iat` (server clock).
+#[tauri::command]
+pub async fn get_session_info(
+    store: State<'_, AuthStore>,
+) -> Result<Option<SessionInfo>, AuthError> {
+    let _guard = store.token.lock().await;
+    let envelope = store
+        .with_credentials(|changes| match read_account_index(changes)?.active {
+            Some(account_id) => read_account_envelope(&account_id, changes),
+            None => Ok(None),
+        })
+        .await?;
+    let Some(envelope) = envelope else {
+        return Ok(None);
+    };
+    let claims = decode_claims(&envelope.token.token)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let clock_skew = envelope.stored_at.zip(claims.iat).map(|(stored_at, iat)| stored_at - iat);
+    let expires_at = claims.exp.or(envelope.token.expires_at);
+    let server_now = now - clock_skew.unwrap_or(0);
+    let expires_in = expires_at.map(|exp| exp - server_now);
+
+    Ok(Some(SessionInfo {
+        user_id: claims.sub,
+        email: claims.email,
+        name: claims.name,
+        issuer: claims.iss,
+        audience: match claims.aud {
+            Some(Audience::One(aud)) => vec![aud],
+            Some(Audience::Many(aud)) => aud,
+            None => Vec::new(),
+        },
+        issued_at: claims.iat,
+        expires_at,
+        expires_in,
+        expired: expires_in.is_some_and(|secs| secs <= 0),
+        clock_skew,
+    }))
+}
+
+/// Accounts with a stored token, the active one flagged
+#[tauri::command]
+pub async fn list_accounts(store: State<'_, AuthStore>) -> Result<Vec<AccountSummary>, AuthError> {
+    let _guard = store.token.lock().await;
+    let index = store.with_credentials(read_account_index).await?;
+    Ok(index
+        .accounts
+        .into_iter()
+        .map(|account| AccountSummary {
+            active: index.active.as_deref() == Some(account.id.as_str()),
+            account,
+        })
+        .collect())
+}
+
+/// Make another stored account the active one and return its token
 #[tauri::command]
-pub fn clear_auth_token() -> Result<(), String> {
-    let mut auth_token = AUTH_TOKEN.lock().unwrap();
-    *auth_token = None;
-    clear_token_in_keyring()?;
+pub async fn switch_account(
+    app_handle: AppHandle,
+    store: State<'_, AuthStore>,
+    account_id: String,
+) -> Result<AuthToken, AuthError> {
+    let mut cached = store.token.lock().await;
+    let switched_id = account_id.clone();
+    let token = store
+        .with_credentials(move |changes| {
+            let mut index = read_account_index(changes)?;
+            if !index.accounts.iter().any(|a| a.id == account_id) {
+                return Err(AuthError::NotFound(format!("Unknown account: {}", account_id)));
+            }
+            let token = read_account_token(&account_id, changes)?.ok_or_else(|| {
+                AuthError::NotFound(format!("No stored token for account {}", account_id))
+            })?;
+
+            index.active = Some(account_id);
+            write_account_index(&index)?;
+            Ok(token)
+        })
+        .await?;
+
+    store.publish(&mut cached, Some(token.clone()));
+    drop(cached);
+
+    // Every window (e.g. the quick-add panel) must drop the previous account's bearer
+    let _ = app_handle.emit(
+        "auth-state-changed",
+        AuthStateChanged {
+            logged_in: true,
+            reason: AuthStateReason::AccountSwitched,
+            account_id: Some(switched_id),
+        },
+    );
+    resume_sync(&app_handle).await;
+    Ok(token)
+}
+
+/// Start the token manager, which refreshes the JWT ahead of its expiry.
+/// Calling it again replaces the running manager (e.g. after a frontend reload).
+#[tauri::command]
+pub fn start_token_refresh(app_handle: AppHandle, api_url: String) -> Result<(), AuthError> {
+    let mut manager = TOKEN_MANAGER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = manager.take() {
+        handle.abort();
+    }
+
+    *manager = Some(tauri::async_runtime::spawn(run_token_manager(
+        app_handle, api_url,
+    )));
     Ok(())
 }
 
-/// Handle deep link callback
+/// Watch `expires_at` of the stored token and refresh it before it expires
+async fn run_token_manager(app_handle: AppHandle, api_url: String) {
+    let store = app_handle.state::<AuthStore>();
+    let mut token_rx = store.watch.subscribe();
+
+    loop {
+        let current = match store.token().await {
+            Ok(token) => token,
+            Err(e) => {
+                eprintln!("Token manager could not read auth token: {}", e);
+                None
+            }
+        };
+
+        // Nothing to refresh until someone logs in
+        let Some(current) = current else {
+            let _ = token_rx.changed().await;
+            continue;
+        };
+        let Some(expires_at) = current.expires_at else {
+            let _ = token_rx.changed().await;
+            continue;
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let refresh_at = expires_at - REFRESH_LEEWAY_SECS;
+        if refresh_at > now {
+            let wait = (refresh_at - now).min(REFRESH_CHECK_SECS) as u64;
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(wait)) => {}
+                _ = token_rx.changed() => {}
+            }
+            continue;
+        }
+
+        // The refresh route only accepts a still-valid token
+        if expires_at <= now {
+            println!("Auth token expired, waiting for a new login");
+            let _ = token_rx.changed().await;
+            continue;
+        }
+
+        match refresh_token(&api_url, &current.token).await {
+            Ok(refreshed) => {
+                // Don't overwrite a token set while the request was in flight
+                let mut cached = store.token.lock().await;
+                let unchanged = matches!(
+                    &*cached,
+                    CachedToken::Loaded(Some(stored)) if stored.token == current.token
+                );
+                if !unchanged {
+                    continue;
+                }
+
+                let stored = refreshed.clone();
+                let written = store
+                    .with_credentials(move |changes| write_stored_token(&stored, changes))
+                    .await;
+                if let Err(e) = written {
+                    eprintln!("Failed to store refreshed token: {}", e);
+                }
+                store.publish(&mut cached, Some(refreshed.clone()));
+                drop(cached);
+
+                println!("Auth token refreshed");
+                let _ = app_handle.emit("auth-token-refreshed", refreshed);
+            }
+            Err(e) => {
+                eprintln!("Token refresh failed: {}", e);
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(REFRESH_RETRY_SECS)) => {}
+                    _ = token_rx.changed() => {}
+                }
+            }
+        }
+    }
+}
+
+/// Exchange a still-valid token for a fresh one
+async fn refresh_token(api_url: &str, token: &str) -> Result<AuthToken, AuthError> {
+    let client = reqwest::Client::new();
+
+    let url = format!("{}/api/auth/tauri/refresh", api_url);
+
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+        .map_err(|e| AuthError::Backend(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AuthError::from_response("Refresh failed", status, &error_text));
+    }
+
+    let data = response
+        .json::<RefreshResponse>()
+        .await
+        .map_err(|e| AuthError::Corrupt(format!("Invalid refresh response: {}", e)))?;
+
+    Ok(AuthToken {
+        token: data.token,
+        expires_at: data.expires_at,
+    })
+}
+
+/// Build the Google authorization URL for a new flow, with an S256 PKCE challenge.
+/// The `state` is a short-lived token signed by the server (`/api/auth/tauri/state`),
+/// so the value that round-trips through Google is checked both here, against the
+/// pending flow, and by the server at exchange. The verifier stays in Rust until then.
+#[tauri::command]
+pub async fn build_oauth_url(
+    api_url: String,
+    client_id: String,
+    redirect_uri: String,
+) -> Result<String, AuthError> {
+    if !is_loopback_redirect(&redirect_uri) {
+        return Err(AuthError::Denied(format!(
+            "redirect_uri not allowed (only the loopback listener is): {}",
+            redirect_uri
+        )));
+    }
+
+    let mut url = Url::parse(GOOGLE_AUTH_URL).map_err(|e| AuthError::Backend(e.to_string()))?;
+    let state = fetch_server_state(&api_url).await?;
+    let code_challenge = OAUTH_STATE.begin(state.clone(), redirect_uri.clone());
+
+    url.query_pairs_mut()
+        .append_pair("client_id", &client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", &GOOGLE_SCOPES.join(" "))
+        .append_pair("access_type", "offline")
+        .append_pair("prompt", "consent")
+        .append_pair("state", &state)
+        .append_pair("code_challenge", &code_challenge)
+        .append_pair("code_challenge_method", "S256");
+
+    Ok(url.into())
+}
+
+/// `http://127.0.0.1|localhost:<port>/callback`, the only redirect `/api/auth/tauri/token`
+/// accepts. A `miniorg://oauth/callback` deep link can't complete a login.
+fn is_loopback_redirect(redirect_uri: &str) -> bool {
+    Url::parse(redirect_uri).is_ok_and(|url| {
+        url.scheme() == "http"
+            && matches!(url.host_str(), Some("127.0.0.1" | "localhost"))
+            && url.path() == "/callback"
+    })
+}
+
+/// A signed OAuth state from the server, which the token exchange endpoint verifies
+async fn fetch_server_state(api_url: &str) -> Result<String, AuthError> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/auth/tauri/state", api_url))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+        .map_err(|e| AuthError::Backend(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AuthError::from_response(
+            "Failed to create OAuth state",
+            status,
+            &error_text,
+        ));
+    }
+    Ok(response
+        .json::<ServerStateResponse>()
+        .await
+        .map_err(|e| AuthError::Corrupt(format!("Invalid state response: {}", e)))?
+        .state)
+}
+
+/// Exchange the code received for the flow identified by `state`,
+/// sending the verifier stored for that flow
+#[tauri::command]
+pub async fn exchange_oauth_code(
+    api_url: String,
+    code: String,
+    state: String,
+) -> Result<OAuthSession, AuthError> {
+    let flow = OAUTH_STATE.take_for_exchange(&state)?;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/auth/tauri/token", api_url))
+        .json(&serde_json::json!({
+            "code": code,
+            "redirect_uri": flow.redirect_uri,
+            "code_verifier": flow.code_verifier,
+            "state": state,
+        }))
+        .send()
+        .await
+        .map_err(|e| AuthError::Backend(e.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AuthError::from_response("Token exchange failed", status, &error_text));
+    }
+
+    response
+        .json::<OAuthSession>()
+        .await
+        .map_err(|e| AuthError::Corrupt(format!("Invalid token response: {}", e)))
+}
+
+/// What became of a callback handed to `dispatch_oauth_callback`
+#[derive(Debug, PartialEq)]
+enum CallbackOutcome {
+    /// The code was forwarded to the main window (or already had been, for a duplicate)
+    Forwarded,
+    /// The provider reported an error; the flow is over
+    Failed(String),
+    /// Not a callback of a pending flow (unknown, missing or expired state).
+    /// The pending flow, if any, keeps waiting for its own callback.
+    Rejected(String),
+}
+
+/// Forward a provider callback to the main window. Unknown states are rejected with
+/// an `oauth-error` of "invalid_state", which doesn't end the flow in progress.
+fn dispatch_oauth_callback(app: &AppHandle, params: CallbackParams) -> CallbackOutcome {
+    match OAUTH_STATE.verify_callback(params.state.as_deref()) {
+        Ok(CallbackMatch::Accepted) => {}
+        Ok(CallbackMatch::Duplicate) => {
+            println!("Ignoring duplicate OAuth callback");
+            return CallbackOutcome::Forwarded;
+        }
+        Err(e) => {
+            eprintln!("Rejected OAuth callback: {}", e);
+            let _ = app.emit_to("main", "oauth-error", "invalid_state".to_string());
+            return CallbackOutcome::Rejected(e);
+        }
+    }
+
+    if let Some(code) = params.code {
+        let payload = OAuthCallbackPayload {
+            code,
+            state: params.state,
+        };
+        let _ = app.emit_to("main", "oauth-code-received", payload);
+        CallbackOutcome::Forwarded
+    } else if let Some(err) = params.error {
+        let _ = app.emit_to("main", "oauth-error", err.clone());
+        CallbackOutcome::Failed(params.error_description.unwrap_or(err))
+    } else {
+        let message = "Invalid OAuth callback".to_string();
+        let _ = app.emit_to("main", "oauth-error", message.clone());
+        CallbackOutcome::Failed(message)
+    }
+}
+
+/// Handle a `miniorg://oauth/callback` deep link, checked against the pending flows like
+/// any callback. `build_oauth_url` only issues loopback redirects, so none is expected.
 pub fn handle_deep_link(app: &AppHandle, url: String) {
     println!("Deep link received: {}", url);
     
     // Parse URL and extract OAuth code
-    if let Ok(parsed_url) = url::Url::parse(&url) {
-        if let Some(code) = parsed_url.query_pairs().find(|(key, _)| key == "code") {
-            let state = parsed_url
-                .query_pairs()
-                .find(|(key, _)| key == "state")
-                .map(|(_, value)| value.to_string());
-            let payload = OAuthCallbackPayload {
-                code: code.1.to_string(),
-                state,
-            };
-            let _ = app.emit_to("main", "oauth-code-received", payload);
-        } else if let Some(error) = parsed_url.query_pairs().find(|(key, _)| key == "error") {
-            let _ = app.emit_to("main", "oauth-error", error.1.to_string());
-        }
+    if let Some(params) = Url::parse(&url).ok().and_then(|url| callback_params(&url)) {
+        let _ = dispatch_oauth_callback(app, params);
     }
 }
 
+/// OAuth parameters of a callback URL, if it carries a code or an error
+fn callback_params(url: &Url) -> Option<CallbackParams> {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    let params = CallbackParams {
+        code: param("code"),
+        state: param("state"),
+        error: param("error"),
+        error_description: param("error_description"),
+    };
+    (params.code.is_some() || params.error.is_some()).then_some(params)
+}
+
+/// A running loopback listener and the channel that cancels it
+struct LoopbackListener {
+    cancel_tx: oneshot::Sender<()>,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
 /// Start a loopback HTTP listener for Google OAuth (desktop flow).
 /// Returns the redirect URI (http://127.0.0.1:<port>/callback) to use in the auth request.
+/// Requests to other paths (favicon, prefetches) are answered with a 404 and ignored;
+/// callbacks whose state isn't pending get the error page and an `oauth-error` of
+/// "invalid_state", and the listener keeps waiting.
+/// Emits `oauth-error` with "timeout" if no callback arrives within `timeout_secs`
+/// (default 5 minutes), or "cancelled" after `cancel_oauth_listener`.
+/// The page shown in the browser follows `locale` (e.g. `navigator.language`).
 #[tauri::command]
-pub async fn start_oauth_listener(app_handle: AppHandle) -> Result<String, String> {
+pub async fn start_oauth_listener(
+    app_handle: AppHandle,
+    timeout_secs: Option<u64>,
+    locale: Option<String>,
+) -> Result<String, AuthError> {
     let listener = TcpListener::bind("127.0.0.1:0")
         .await
-        .map_err(|e| format!("Failed to bind loopback listener: {}", e))?;
+        .map_err(|e| AuthError::Backend(format!("Failed to bind loopback listener: {}", e)))?;
 
     let port = listener
         .local_addr()
-        .map_err(|e| format!("Failed to read loopback port: {}", e))?
+        .map_err(|e| AuthError::Backend(format!("Failed to read loopback port: {}", e)))?
         .port();
 
     let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
-    let app_handle_clone = app_handle.clone();
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(OAUTH_LISTENER_TIMEOUT_SECS));
+    let locale = PageLocale::from_tag(locale.as_deref());
+    let (cancel_tx, cancel_rx) = oneshot::channel();
 
-    tauri::async_runtime::spawn(async move {
-        // Accept a single request then close
-        if let Ok((mut socket, _)) = listener.accept().await {
-            let mut buffer = [0u8; 4096];
-            let mut request = String::new();
+    let handle = tauri::async_runtime::spawn(run_loopback_listener(
+        app_handle,
+        listener,
+        timeout,
+        locale,
+        cancel_rx,
+    ));
 
-            if let Ok(n) = socket.read(&mut buffer).await {
-                request = String::from_utf8_lossy(&buffer[..n]).into_owned();
-            }
+    // A new flow replaces the previous listener
+    if let Some(previous) = OAUTH_LISTENER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(LoopbackListener { cancel_tx, handle })
+    {
+        previous.handle.abort();
+    }
+
+    Ok(redirect_uri)
+}
+
+/// Stop the loopback listener; the flow fails with an `oauth-error` of "cancelled"
+#[tauri::command]
+pub fn cancel_oauth_listener() {
+    if let Some(listener) = OAUTH_LISTENER.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = listener.cancel_tx.send(());
+    }
+}
 
-            let mut code: Option<String> = None;
-            let mut error: Option<String> = None;
-            let mut state: Option<String> = None;
-
-            if let Some(first_line) = request.lines().next() {
-                if let Some(path) = first_line.split_whitespace().nth(1) {
-                    if let Ok(parsed) = Url::parse(&format!("http://localhost{}", path)) {
-                        if let Some((_, c)) = parsed.query_pairs().find(|(k, _)| k == "code") {
-                            code = Some(c.into_owned());
-                            state = parsed
-                                .query_pairs()
-                                .find(|(k, _)| k == "state")
-                                .map(|(_, value)| value.into_owned());
-                        } else if let Some((_, e)) =
-                            parsed.query_pairs().find(|(k, _)| k == "error")
-                        {
-                            error = Some(e.into_owned());
-                        }
+async fn run_loopback_listener(
+    app_handle: AppHandle,
+    listener: TcpListener,
+    timeout: Duration,
+    locale: PageLocale,
+    mut cancel_rx: oneshot::Receiver<()>,
+) {
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+
+    // Each connection is read in its own task, so idle ones (e.g. speculative
+    // preconnects) don't hold up the callback behind them
+    let (callback_tx, mut callback_rx) = mpsc::channel::<(TcpStream, CallbackParams)>(8);
+
+    loop {
+        tokio::select! {
+            _ = &mut deadline => {
+                println!("OAuth loopback listener timed out");
+                let _ = app_handle.emit_to("main", "oauth-error", "timeout".to_string());
+                break;
+            }
+            _ = &mut cancel_rx => {
+                println!("OAuth loopback listener cancelled");
+                let _ = app_handle.emit_to("main", "oauth-error", "cancelled".to_string());
+                break;
+            }
+            accepted = listener.accept() => {
+                let Ok((mut socket, _)) = accepted else { continue };
+                let callback_tx = callback_tx.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(params) = read_loopback_callback(&mut socket).await {
+                        let _ = callback_tx.send((socket, params)).await;
+                    }
+                });
+            }
+            Some((mut socket, params)) = callback_rx.recv() => {
+                // Show the outcome in the browser (matching MiniOrg design)
+                match dispatch_oauth_callback(&app_handle, params) {
+                    CallbackOutcome::Forwarded => {
+                        let page = oauth_page::success_page(locale);
+                        write_response(&mut socket, "200 OK", &page).await;
+                        break;
+                    }
+                    CallbackOutcome::Failed(description) => {
+                        let page = oauth_page::error_page(locale, &description);
+                        write_response(&mut socket, "200 OK", &page).await;
+                        break;
+                    }
+                    // A stray request must not consume the listener: keep waiting
+                    // for the callback of the pending flow
+                    CallbackOutcome::Rejected(description) => {
+                        let page = oauth_page::error_page(locale, &description);
+                        write_response(&mut socket, "400 Bad Request", &page).await;
                     }
                 }
             }
+        }
+    }
+}
 
-            // Send HTML response with link to open the app (matching MiniOrg design)
-            let body = r#"
-<!doctype html>
-<html>
-  <head>
-    <meta charset="utf-8">
-    <title>MiniOrg</title>
-    <style>
-      * { box-sizing: border-box; }
-      body {
-        font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
-        display: flex;
-        justify-content: center;
-        align-items: center;
-        min-height: 100vh;
-        margin: 0;
-        background: hsl(40 10% 98%);
-        color: hsl(0 0% 12%);
-      }
-      .container {
-        width: 100%;
-        max-width: 28rem;
-        padding: 2rem;
-        text-align: center;
-      }
-      .icon {
-        width: 48px;
-        height: 48px;
-        margin: 0 auto 1.5rem;
-        background: hsl(142 76% 36%);
-        border-radius: 50%;
-        display: flex;
-        align-items: center;
-        justify-content: center;
-      }
-      .icon svg {
-        width: 24px;
-        height: 24px;
-        color: white;
-      }
-      h1 {
-        font-size: 2.25rem;
-        font-weight: 600;
-        letter-spacing: -0.025em;
-        margin: 0 0 0.5rem;
-      }
-      p {
-        color: hsl(0 0% 45%);
-        margin: 0 0 2.5rem;
-      }
-      .btn {
-        display: flex;
-        align-items: center;
-        justify-content: center;
-        gap: 0.75rem;
-        width: 100%;
-        padding: 0.75rem 1rem;
-        background: white;
-        border: 1px solid hsl(0 0% 90%);
-        border-radius: 0.5rem;
-        font-size: 1rem;
-        font-weight: 500;
-        color: hsl(0 0% 12%);
-        text-decoration: none;
-        box-shadow: 0 1px 2px rgba(0,0,0,0.05);
-        transition: background 0.2s;
-        cursor: pointer;
-      }
-      .btn:hover {
-        background: hsl(0 0% 96%);
-      }
-      .hint {
-        font-size: 0.875rem;
-        color: hsl(0 0% 45%);
-        margin-top: 1rem;
-      }
-    </style>
-  </head>
-  <body>
-    <div class="container">
-      <div class="icon">
-        <svg fill="none" stroke="currentColor" stroke-width="3" viewBox="0 0 24 24">
-          <path stroke-linecap="round" stroke-linejoin="round" d="M5 13l4 4L19 7"/>
-        </svg>
-      </div>
-      <h1>MiniOrg</h1>
-      <p>Connexion réussie</p>
-      <a href="miniorg://open" class="btn">
-        <svg width="20" height="20" fill="none" stroke="currentColor" stroke-width="2" viewBox="0 0 24 24">
-          <path stroke-linecap="round" stroke-linejoin="round" d="M10 6H6a2 2 0 00-2 2v10a2 2 0 002 2h10a2 2 0 002-2v-4M14 4h6m0 0v6m0-6L10 14"/>
-        </svg>
-        Ouvrir MiniOrg
-      </a>
-      <p class="hint">Vous pouvez fermer cet onglet</p>
-    </div>
-  </body>
-</html>
-"#;
-            let response = format!(
-                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-                body.len(),
-                body
-            );
-
-            let _ = socket.write_all(response.as_bytes()).await;
-            let _ = socket.shutdown().await;
-
-            if let Some(code) = code {
-                let payload = OAuthCallbackPayload { code, state };
-                let _ = app_handle_clone.emit_to("main", "oauth-code-received", payload);
-            } else if let Some(err) = error {
-                let _ = app_handle_clone.emit_to("main", "oauth-error", err);
-            } else {
-                let _ = app_handle_clone.emit_to(
-                    "main",
-                    "oauth-error",
-                    "Invalid OAuth callback".to_string(),
-                );
-            }
+/// Read one request. Returns the callback parameters if it was for `/callback`,
+/// otherwise answers it and returns `None`.
+async fn read_loopback_callback(socket: &mut TcpStream) -> Option<CallbackParams> {
+    // Don't keep an idle connection (e.g. a speculative preconnect) open forever
+    let request = match tokio::time::timeout(LOOPBACK_READ_TIMEOUT, read_request(socket)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(e)) => {
+            eprintln!("Invalid loopback request: {}", e);
+            write_response(socket, "400 Bad Request", "").await;
+            return None;
         }
-    });
+        Err(_) => return None,
+    };
 
-    Ok(redirect_uri)
+    let Some(params) = CallbackParams::from_request(&request, "/callback") else {
+        write_response(socket, "404 Not Found", "").await;
+        return None;
+    };
+
+    Some(params)
 }
@@ -1,59 +1,901 @@
+use crate::auth;
+use crate::connectivity::ConnectivityMonitor;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::time::{interval, Duration};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::{oneshot, watch, Mutex, Notify};
+use tokio::time::{interval_at, sleep_until, Duration, Instant, Interval, MissedTickBehavior};
+
+/// File (in the app data dir) holding the persisted `SyncConfig`
+pub const SYNC_CONFIG_FILE: &str = "sync_config.json";
+/// File (in the app data dir) holding the recent sync attempts
+pub const SYNC_HISTORY_FILE: &str = "sync_history.json";
+/// Number of sync attempts kept in the history
+const SYNC_HISTORY_LIMIT: usize = 50;
+/// Longest accepted sync interval (one day)
+const MAX_SYNC_INTERVAL_MINUTES: u64 = 24 * 60;
+/// Longest accepted sync window on either side of today (ten years)
+const MAX_SYNC_WINDOW_DAYS: i64 = 3650;
+
+/// Wait for focus/wake triggers to settle before syncing
+const TRIGGER_DEBOUNCE: Duration = Duration::from_secs(3);
+/// Ignore focus triggers when the last sync attempt is more recent than this
+const MIN_TRIGGER_GAP_SECS: i64 = 2 * 60;
+/// How often the wall clock is compared against the monotonic clock
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A wall-clock jump larger than this (beyond monotonic time) means we woke from sleep
+const WAKE_JUMP_THRESHOLD_SECS: i64 = 2 * 60;
+
+/// React Query keys refreshed after a successful sync
+const CALENDAR_QUERY_KEYS: &[&str] = &["calendar-events"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InvalidateQueriesPayload {
+    query_keys: Vec<String>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncStatus {
+    pub is_running: bool,
     pub is_syncing: bool,
+    /// Whether the API is reachable (syncs are skipped while offline)
+    pub is_online: bool,
     pub last_sync: Option<String>,
     pub error: Option<String>,
+    /// Outcome of the last sync for each calendar connection
+    pub calendars: Vec<CalendarSyncResult>,
+    /// Retries made so far for the current failing sync (0 when healthy)
+    pub retry_count: u32,
+    /// When the next retry is scheduled, if any
+    pub next_attempt: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionSyncStatus {
+    Success,
+    Error,
+}
+
+/// Per-connection outcome, as shown next to each calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarSyncResult {
+    pub connection_id: String,
+    pub connection_name: String,
+    pub status: ConnectionSyncStatus,
+    pub error: Option<String>,
+    /// When this outcome was recorded
+    pub synced_at: String,
+    /// Last time this connection synced successfully
+    pub last_success: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncOutcome {
+    Success,
+    Failed,
+}
+
+/// One sync attempt (retries included), as kept in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncAttempt {
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub outcome: SyncOutcome,
+    pub error: Option<String>,
+    /// Per-calendar results after this attempt (empty when the request failed)
+    pub calendars: Vec<CalendarSyncResult>,
+}
+
+/// Recent sync attempts, newest first, persisted in the app data dir
+pub struct SyncHistory {
+    path: PathBuf,
+    attempts: std::sync::Mutex<Vec<SyncAttempt>>,
+}
+
+impl SyncHistory {
+    /// Load the history from disk, starting empty if missing or unreadable
+    fn load(path: PathBuf) -> Self {
+        let attempts = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Invalid sync history, starting over: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path,
+            attempts: std::sync::Mutex::new(attempts),
+        }
+    }
+
+    fn record(&self, attempt: SyncAttempt) {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.insert(0, attempt);
+        attempts.truncate(SYNC_HISTORY_LIMIT);
+
+        if let Err(e) = self.save(&attempts) {
+            eprintln!("Failed to save sync history: {}", e);
+        }
+    }
+
+    fn save(&self, attempts: &[SyncAttempt]) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let payload = serde_json::to_string(attempts).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, payload).map_err(|e| e.to_string())
+    }
+
+    /// The most recent attempts, newest first
+    pub fn recent(&self, limit: usize) -> Vec<SyncAttempt> {
+        let attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.iter().take(limit).cloned().collect()
+    }
+
+    /// Seed the in-memory status from the history at launch
+    fn restore_status(&self, status: &mut SyncStatus) {
+        let attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(latest) = attempts.first() {
+            status.error = latest.error.clone();
+        }
+        if let Some(success) = attempts.iter().find(|a| a.outcome == SyncOutcome::Success) {
+            // `last_sync` is recorded when the request completes
+            let finished = chrono::DateTime::parse_from_rfc3339(&success.started_at)
+                .map(|at| at + chrono::Duration::milliseconds(success.duration_ms as i64))
+                .map(|at| at.with_timezone(&chrono::Utc).to_rfc3339())
+                .unwrap_or_else(|_| success.started_at.clone());
+            status.last_sync = Some(finished);
+            status.calendars = success.calendars.clone();
+        }
+    }
+}
+
+/// What the sync helpers need besides the request itself
+#[derive(Clone)]
+struct SyncContext {
+    app_handle: AppHandle,
+    history: Arc<SyncHistory>,
+}
+
+/// Why a sync failed, used to decide whether it is worth retrying
+#[derive(Debug, Clone)]
+pub enum SyncError {
+    /// Couldn't reach the API (offline, DNS, connection reset, ...)
+    Network(String),
+    /// 5xx from the API
+    Server { status: u16, message: String },
+    /// 429 from the API, with the delay requested in `Retry-After`
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// 401: the token was rejected, retrying with it is pointless
+    Unauthorized(String),
+    /// Any other rejected request or unreadable response
+    Request(String),
+    NotLoggedIn,
+    AlreadySyncing,
+}
+
+impl SyncError {
+    /// Network errors, 5xx and 429 are worth retrying
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SyncError::Network(_) | SyncError::Server { .. } | SyncError::RateLimited { .. }
+        )
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            SyncError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Network(e) => write!(f, "Network error: {}", e),
+            SyncError::Server { status, message } => {
+                write!(f, "Sync failed ({}): {}", status, message)
+            }
+            SyncError::RateLimited { message, .. } => write!(f, "Rate limited: {}", message),
+            SyncError::Unauthorized(message) => write!(f, "Unauthorized: {}", message),
+            SyncError::Request(message) => write!(f, "Sync failed: {}", message),
+            SyncError::NotLoggedIn => write!(f, "Not logged in"),
+            SyncError::AlreadySyncing => write!(f, "Sync already in progress"),
+        }
+    }
+}
+
+/// Jittered exponential backoff for transient sync failures
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Background loop: keep trying for a while before falling back to the interval
+    pub const BACKGROUND: RetryPolicy = RetryPolicy {
+        max_retries: 5,
+        base_delay: Duration::from_secs(30),
+        max_delay: Duration::from_secs(10 * 60),
+    };
+
+    /// Manual trigger: the caller is waiting, so only a couple of quick retries
+    pub const MANUAL: RetryPolicy = RetryPolicy {
+        max_retries: 2,
+        base_delay: Duration::from_secs(2),
+        max_delay: Duration::from_secs(10),
+    };
+
+    /// Delay before retry number `retry` (starting at 1), or `None` when the
+    /// server asks us to wait longer than this policy allows
+    fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+        // Equal jitter: half fixed, half random
+        let half = exp / 2;
+        let jitter = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        Some(half + Duration::from_millis(jitter))
+    }
+}
+
+/// Response body of `/api/calendar-sync`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncResponse {
+    synced_count: usize,
+    total_count: usize,
+    #[serde(default)]
+    results: Vec<ConnectionResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConnectionResult {
+    connection_id: String,
+    connection_name: String,
+    status: ConnectionSyncStatus,
+    error: Option<String>,
+}
+
+/// User-editable sync settings, persisted on disk and applied live to the loop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Days in the past to sync
+    pub lookbehind_days: i64,
+    /// Days in the future to sync
+    pub lookahead_days: i64,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 15,
+            lookbehind_days: 30,
+            lookahead_days: 30,
+        }
+    }
+}
+
+impl SyncConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.interval_minutes == 0 {
+            return Err("Sync interval must be at least 1 minute".to_string());
+        }
+        if self.interval_minutes > MAX_SYNC_INTERVAL_MINUTES {
+            return Err(format!(
+                "Sync interval must be at most {} minutes",
+                MAX_SYNC_INTERVAL_MINUTES
+            ));
+        }
+        if self.lookbehind_days < 0 || self.lookahead_days < 0 {
+            return Err("Sync window must not be negative".to_string());
+        }
+        if self.lookbehind_days.max(self.lookahead_days) > MAX_SYNC_WINDOW_DAYS {
+            return Err(format!("Sync window must be at most {} days", MAX_SYNC_WINDOW_DAYS));
+        }
+        Ok(())
+    }
+
+    /// Load the config from disk, falling back to defaults if missing, unreadable
+    /// or out of range (e.g. saved by a build that didn't check the bounds)
+    fn load(path: &Path) -> Self {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Self::default(),
+        };
+        match serde_json::from_str::<Self>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|config| config.validate().map(|_| config))
+        {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Invalid sync config, using defaults: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let payload = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, payload).map_err(|e| format!("Failed to save sync config: {}", e))
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_minutes * 60)
+    }
+
+    /// JSON body for `/api/calendar-sync`: the sync window around now
+    fn request_body(&self) -> Result<serde_json::Value, String> {
+        let now = chrono::Utc::now();
+        let start = chrono::Duration::try_days(self.lookbehind_days)
+            .and_then(|days| now.checked_sub_signed(days));
+        let end = chrono::Duration::try_days(self.lookahead_days)
+            .and_then(|days| now.checked_add_signed(days));
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err("Sync window out of range".to_string());
+        };
+        Ok(serde_json::json!({
+            "startDate": start.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            "endDate": end.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        }))
+    }
 }
 
 // Global sync state
 lazy_static::lazy_static! {
     static ref SYNC_STATE: Arc<Mutex<SyncStatus>> = Arc::new(Mutex::new(SyncStatus {
+        is_running: false,
         is_syncing: false,
+        is_online: true,
         last_sync: None,
         error: None,
+        calendars: Vec::new(),
+        retry_count: 0,
+        next_attempt: None,
     }));
 }
 
-/// Start background calendar sync (every 15 minutes)
-pub async fn start_background_sync(api_url: String, auth_token: String) {
-    let mut interval = interval(Duration::from_secs(15 * 60)); // 15 minutes
+/// Background sync service, held in Tauri state.
+/// Owns the only sync loop of the app: starting it again while it runs is a no-op,
+/// and a restart waits for the previous loop to exit before spawning a new one.
+pub struct SyncService {
+    ctx: SyncContext,
+    inner: Mutex<ServiceState>,
+    config_path: PathBuf,
+    config_tx: watch::Sender<SyncConfig>,
+    /// Focus triggers, picked up (debounced) by the running loop
+    sync_requests: Arc<Notify>,
+}
 
-    tokio::spawn(async move {
-        loop {
-            interval.tick().await;
-            
-            println!("Running background calendar sync...");
-            
-            let mut state = SYNC_STATE.lock().await;
-            state.is_syncing = true;
-            state.error = None;
-            drop(state); // Release lock before async operation
+#[derive(Default)]
+struct ServiceState {
+    api_url: Option<String>,
+    running: Option<RunningSync>,
+    /// The loop was stopped by a logout and resumes on the next login
+    stopped_for_logout: bool,
+}
 
-            match sync_calendar(&api_url, &auth_token).await {
-                Ok(_) => {
-                    let mut state = SYNC_STATE.lock().await;
-                    state.is_syncing = false;
-                    state.last_sync = Some(chrono::Utc::now().to_rfc3339());
-                    println!("Calendar sync completed successfully");
+struct RunningSync {
+    api_url: String,
+    stop_tx: oneshot::Sender<()>,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl RunningSync {
+    fn is_alive(&self) -> bool {
+        !self.handle.inner().is_finished()
+    }
+
+    /// Signal the loop to stop and wait until it has exited
+    async fn shutdown(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.await;
+    }
+}
+
+impl SyncService {
+    /// Create the service, loading the persisted config from `config_path`
+    pub fn new(app_handle: AppHandle, config_path: PathBuf, history_path: PathBuf) -> Self {
+        let config = SyncConfig::load(&config_path);
+        let history = SyncHistory::load(history_path);
+        if let Ok(mut state) = SYNC_STATE.try_lock() {
+            history.restore_status(&mut state);
+        }
+
+        Self {
+            ctx: SyncContext {
+                app_handle,
+                history: Arc::new(history),
+            },
+            inner: Mutex::new(ServiceState::default()),
+            config_path,
+            config_tx: watch::channel(config).0,
+            sync_requests: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Ask the running loop for a sync soon (e.g. the main window regained focus).
+    /// Bursts are debounced and skipped if a sync just ran.
+    pub fn request_sync(&self) {
+        self.sync_requests.notify_one();
+    }
+
+    pub fn config(&self) -> SyncConfig {
+        self.config_tx.borrow().clone()
+    }
+
+    /// Persist a new config and hand it to the running loop
+    pub fn update_config(&self, config: SyncConfig) -> Result<(), String> {
+        config.validate()?;
+        config.save(&self.config_path)?;
+        self.config_tx.send_replace(config);
+        Ok(())
+    }
+
+    /// Start the sync loop, unless it is already running against the same API
+    pub async fn start(&self, api_url: String) {
+        let mut inner = self.inner.lock().await;
+        if let Some(running) = inner.running.as_ref() {
+            if running.api_url == api_url && running.is_alive() {
+                return;
+            }
+        }
+        self.spawn_loop(&mut inner, api_url).await;
+    }
+
+    /// Stop the sync loop. Returns `false` if it wasn't running.
+    pub async fn stop(&self) -> bool {
+        let running = {
+            let mut inner = self.inner.lock().await;
+            inner.stopped_for_logout = false;
+            inner.running.take()
+        };
+        match running {
+            Some(running) => {
+                running.shutdown().await;
+                println!("Calendar sync service stopped");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop the loop when the user logs out, so `resume_after_login` starts it again
+    pub async fn stop_for_logout(&self) {
+        let mut inner = self.inner.lock().await;
+        if let Some(running) = inner.running.take() {
+            running.shutdown().await;
+            inner.stopped_for_logout = true;
+            println!("Calendar sync service stopped for logout");
+        }
+    }
+
+    /// Start the loop again on the last API URL if a logout stopped it
+    pub async fn resume_after_login(&self) {
+        let mut inner = self.inner.lock().await;
+        if !inner.stopped_for_logout {
+            return;
+        }
+        if let Some(api_url) = inner.api_url.clone() {
+            self.spawn_loop(&mut inner, api_url).await;
+        }
+    }
+
+    /// Stop and start the loop again, reusing the last API URL when none is given
+    pub async fn restart(&self, api_url: Option<String>) -> Result<(), String> {
+        let mut inner = self.inner.lock().await;
+        let api_url = api_url
+            .or_else(|| inner.api_url.clone())
+            .ok_or_else(|| "Sync service has never been started".to_string())?;
+        self.spawn_loop(&mut inner, api_url).await;
+        Ok(())
+    }
+
+    async fn spawn_loop(&self, inner: &mut ServiceState, api_url: String) {
+        if let Some(running) = inner.running.take() {
+            running.shutdown().await;
+        }
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let handle = tauri::async_runtime::spawn(run_background_sync(
+            self.ctx.clone(),
+            api_url.clone(),
+            self.config_tx.subscribe(),
+            self.sync_requests.clone(),
+            stop_rx,
+        ));
+
+        SYNC_STATE.lock().await.is_running = true;
+        inner.api_url = Some(api_url.clone());
+        inner.stopped_for_logout = false;
+        inner.running = Some(RunningSync {
+            api_url,
+            stop_tx,
+            handle,
+        });
+        println!("Calendar sync service started");
+    }
+}
+
+/// Run the sync loop until a stop is requested
+async fn run_background_sync(
+    ctx: SyncContext,
+    api_url: String,
+    config_rx: watch::Receiver<SyncConfig>,
+    sync_requests: Arc<Notify>,
+    stop_rx: oneshot::Receiver<()>,
+) {
+    tokio::select! {
+        _ = sync_loop(&ctx, &api_url, config_rx, sync_requests) => {}
+        _ = stop_rx => {}
+    }
+
+    // The loop may have been dropped mid-sync
+    let mut state = SYNC_STATE.lock().await;
+    state.is_running = false;
+    state.is_syncing = false;
+}
+
+/// Interval that never bursts missed ticks (e.g. after the laptop sleeps)
+fn sync_interval(start: Instant, period: Duration) -> Interval {
+    let mut interval = interval_at(start, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+/// Background calendar sync (every `interval_minutes`, 15 by default).
+/// The token is read from the auth store on every tick, so re-logins and refreshes
+/// are picked up; the loop pauses while logged out or disabled in the config.
+/// Focus triggers and wake-from-sleep (wall-clock jumps) run a debounced extra sync.
+async fn sync_loop(
+    ctx: &SyncContext,
+    api_url: &str,
+    mut config_rx: watch::Receiver<SyncConfig>,
+    sync_requests: Arc<Notify>,
+) {
+    let mut config = config_rx.borrow_and_update().clone();
+    let mut interval = sync_interval(Instant::now(), config.interval());
+    let mut token_rx = auth::subscribe_token(&ctx.app_handle);
+
+    let mut clock_check = sync_interval(Instant::now(), CLOCK_CHECK_INTERVAL);
+    let mut last_clock_check = (Instant::now(), chrono::Utc::now());
+    // Deadline of a debounced focus/wake sync, and whether a wake is among its causes
+    let mut trigger_at: Option<Instant> = None;
+    let mut trigger_woke = false;
+    let mut last_attempt: Option<chrono::DateTime<chrono::Utc>> = None;
+
+    let monitor = ConnectivityMonitor::for_api(api_url).unwrap_or_else(|e| {
+        eprintln!("Connectivity monitor disabled: {}", e);
+        ConnectivityMonitor::always_online()
+    });
+    let mut online_rx = monitor.subscribe();
+    // A sync was skipped or failed because we were offline
+    let mut catch_up_pending = false;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = sync_requests.notified() => {
+                trigger_at = Some(Instant::now() + TRIGGER_DEBOUNCE);
+                continue;
+            }
+            _ = clock_check.tick() => {
+                let (last_mono, last_wall) = last_clock_check;
+                let now = (Instant::now(), chrono::Utc::now());
+                last_clock_check = now;
+
+                let wall_elapsed = now.1 - last_wall;
+                let mono_elapsed = chrono::Duration::from_std(now.0 - last_mono)
+                    .unwrap_or_else(|_| chrono::Duration::zero());
+                if (wall_elapsed - mono_elapsed).num_seconds() > WAKE_JUMP_THRESHOLD_SECS {
+                    println!(
+                        "Wall clock jumped by {}s, assuming wake from sleep",
+                        wall_elapsed.num_seconds()
+                    );
+                    trigger_at = Some(Instant::now() + TRIGGER_DEBOUNCE);
+                    trigger_woke = true;
+                }
+                continue;
+            }
+            _ = sleep_until(trigger_at.unwrap_or_else(Instant::now)), if trigger_at.is_some() => {
+                trigger_at = None;
+                let woke = std::mem::take(&mut trigger_woke);
+                let recent = last_attempt
+                    .map(|at| (chrono::Utc::now() - at).num_seconds() < MIN_TRIGGER_GAP_SECS)
+                    .unwrap_or(false);
+                if recent && !woke {
+                    continue;
+                }
+                println!("Calendar sync triggered by {}", if woke { "wake" } else { "focus" });
+            }
+            changed = online_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let online = *online_rx.borrow_and_update();
+                SYNC_STATE.lock().await.is_online = online;
+                if online && catch_up_pending {
+                    println!("Back online, running catch-up calendar sync");
+                    interval.reset_immediately();
                 }
-                Err(e) => {
-                    let mut state = SYNC_STATE.lock().await;
-                    state.is_syncing = false;
-                    state.error = Some(e);
-                    eprintln!("Calendar sync failed");
+                continue;
+            }
+            changed = config_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                let updated = config_rx.borrow_and_update().clone();
+                if updated.interval_minutes != config.interval_minutes {
+                    // The new cadence starts from now
+                    let period = updated.interval();
+                    interval = sync_interval(Instant::now() + period, period);
                 }
+                config = updated;
+                continue;
             }
         }
-    });
+
+        if !config.enabled {
+            continue;
+        }
+
+        if !monitor.is_online() {
+            println!("Offline, skipping calendar sync");
+            catch_up_pending = true;
+            continue;
+        }
+
+        let auth_token = match auth::current_token(&ctx.app_handle).await {
+            Some(token) => token,
+            None => {
+                println!("Calendar sync paused: not logged in");
+                let token = loop {
+                    if token_rx.changed().await.is_err() {
+                        return;
+                    }
+                    if let Some(token) = auth::current_token(&ctx.app_handle).await {
+                        break token;
+                    }
+                };
+                // Resume right away, then keep the regular cadence from here
+                interval.reset();
+                println!("Calendar sync resumed");
+                token
+            }
+        };
+
+        println!("Running background calendar sync...");
+        last_attempt = Some(chrono::Utc::now());
+
+        let policy = RetryPolicy::BACKGROUND;
+        let result =
+            sync_with_retry(ctx, api_url, &auth_token, &config, &policy, Some(&monitor))
+                .await;
+        catch_up_pending = matches!(result, Err(SyncError::Network(_)));
+
+        match result {
+            Ok(_) => println!("Calendar sync completed successfully"),
+            Err(SyncError::Unauthorized(e)) => {
+                // The same token will keep failing: wait for a refresh or re-login
+                eprintln!("Calendar sync unauthorized, waiting for a new token: {}", e);
+                if token_rx.changed().await.is_err() {
+                    return;
+                }
+                interval.reset_immediately();
+            }
+            Err(e) => eprintln!("Calendar sync failed: {}", e),
+        }
+    }
+}
+
+/// Sync, retrying transient failures according to `policy`.
+/// The token is re-read before each retry in case it was refreshed meanwhile.
+/// Emits `sync-finished` / `sync-failed` once the retry state is up to date.
+/// Network errors aren't retried while `monitor` reports offline.
+async fn sync_with_retry(
+    ctx: &SyncContext,
+    api_url: &str,
+    auth_token: &auth::AuthToken,
+    config: &SyncConfig,
+    policy: &RetryPolicy,
+    monitor: Option<&ConnectivityMonitor>,
+) -> Result<(), SyncError> {
+    let mut token = auth_token.token.clone();
+    let mut retries = 0;
+
+    loop {
+        let result = run_sync(ctx, api_url, &token, config).await;
+        if let Err(SyncError::AlreadySyncing) = result {
+            // The running sync owns the retry state
+            return result;
+        }
+
+        let offline = monitor.map(|m| !m.is_online()).unwrap_or(false);
+        let delay = match &result {
+            Err(SyncError::Network(_)) if offline => None,
+            Err(e) if e.is_transient() && retries < policy.max_retries => {
+                policy.delay(retries + 1, e.retry_after())
+            }
+            _ => None,
+        };
+        let Some(delay) = delay else {
+            set_retry_state(0, None).await;
+            match &result {
+                Ok(_) => {
+                    emit_status(&ctx.app_handle, "sync-finished").await;
+                    let payload = InvalidateQueriesPayload {
+                        query_keys: CALENDAR_QUERY_KEYS.iter().map(|k| k.to_string()).collect(),
+                    };
+                    let _ = ctx.app_handle.emit("invalidate-queries", payload);
+                }
+                Err(_) => emit_status(&ctx.app_handle, "sync-failed").await,
+            }
+            return result;
+        };
+
+        retries += 1;
+        let next_attempt = chrono::Utc::now()
+            + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero());
+        set_retry_state(retries, Some(next_attempt.to_rfc3339())).await;
+        emit_status(&ctx.app_handle, "sync-failed").await;
+        if let Err(e) = &result {
+            eprintln!(
+                "Calendar sync failed ({}), retry {}/{} in {}s",
+                e,
+                retries,
+                policy.max_retries,
+                delay.as_secs()
+            );
+        }
+
+        tokio::time::sleep(delay).await;
+
+        token = match auth::current_token(&ctx.app_handle).await {
+            Some(current) => current.token,
+            None => {
+                set_retry_state(0, None).await;
+                emit_status(&ctx.app_handle, "sync-failed").await;
+                return Err(SyncError::NotLoggedIn);
+            }
+        };
+    }
+}
+
+async fn set_retry_state(retry_count: u32, next_attempt: Option<String>) {
+    let mut state = SYNC_STATE.lock().await;
+    state.retry_count = retry_count;
+    state.next_attempt = next_attempt;
+}
+
+/// Emit a sync event to every window, carrying a snapshot of the sync status
+async fn emit_status(app_handle: &AppHandle, event: &str) {
+    let status = SYNC_STATE.lock().await.clone();
+    let _ = app_handle.emit(event, status);
+}
+
+/// Run one sync and record its outcome in the global sync state.
+/// Fails without syncing if another sync is already in progress.
+async fn run_sync(
+    ctx: &SyncContext,
+    api_url: &str,
+    auth_token: &str,
+    config: &SyncConfig,
+) -> Result<(), SyncError> {
+    let mut state = SYNC_STATE.lock().await;
+
+    if state.is_syncing {
+        return Err(SyncError::AlreadySyncing);
+    }
+
+    state.is_syncing = true;
+    state.error = None;
+    let _ = ctx.app_handle.emit("sync-started", state.clone());
+    drop(state); // Release lock before async operation
+
+    let started_at = chrono::Utc::now();
+    let started = Instant::now();
+    let result = sync_calendar(api_url, auth_token, config).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    let mut state = SYNC_STATE.lock().await;
+    state.is_syncing = false;
+    let (result, attempt) = match result {
+        Ok(response) => {
+            let now = chrono::Utc::now().to_rfc3339();
+            println!(
+                "Synced {}/{} calendars",
+                response.synced_count, response.total_count
+            );
+            state.calendars = merge_calendar_results(&state.calendars, response.results, &now);
+            state.last_sync = Some(now);
+            let attempt = SyncAttempt {
+                started_at: started_at.to_rfc3339(),
+                duration_ms,
+                outcome: SyncOutcome::Success,
+                error: None,
+                calendars: state.calendars.clone(),
+            };
+            (Ok(()), attempt)
+        }
+        Err(e) => {
+            state.error = Some(e.to_string());
+            let attempt = SyncAttempt {
+                started_at: started_at.to_rfc3339(),
+                duration_ms,
+                outcome: SyncOutcome::Failed,
+                error: Some(e.to_string()),
+                calendars: Vec::new(),
+            };
+            (Err(e), attempt)
+        }
+    };
+    drop(state);
+
+    ctx.history.record(attempt);
+    result
+}
+
+/// Build the per-calendar list from a sync response, keeping each connection's
+/// last successful sync time from the previous list when it failed this time
+fn merge_calendar_results(
+    previous: &[CalendarSyncResult],
+    results: Vec<ConnectionResult>,
+    synced_at: &str,
+) -> Vec<CalendarSyncResult> {
+    results
+        .into_iter()
+        .map(|result| {
+            let last_success = match result.status {
+                ConnectionSyncStatus::Success => Some(synced_at.to_string()),
+                ConnectionSyncStatus::Error => previous
+                    .iter()
+                    .find(|p| p.connection_id == result.connection_id)
+                    .and_then(|p| p.last_success.clone()),
+            };
+            CalendarSyncResult {
+                connection_id: result.connection_id,
+                connection_name: result.connection_name,
+                status: result.status,
+                error: result.error,
+                synced_at: synced_at.to_string(),
+                last_success,
+            }
+        })
+        .collect()
 }
 
 /// Perform calendar sync by calling the API
-async fn sync_calendar(api_url: &str, auth_token: &str) -> Result<(), String> {
+async fn sync_calendar(
+    api_url: &str,
+    auth_token: &str,
+    config: &SyncConfig,
+) -> Result<SyncResponse, SyncError> {
+    let body = config.request_body().map_err(SyncError::Request)?;
     let client = reqwest::Client::new();
     
     let url = format!("{}/api/calendar-sync", api_url);
@@ -62,48 +904,60 @@ async fn sync_calendar(api_url: &str, auth_token: &str) -> Result<(), String> {
         .post(&url)
         .header("Authorization", format!("Bearer {}", auth_token))
         .header("Content-Type", "application/json")
+        .json(&body)
         .send()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| SyncError::Network(e.to_string()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let message = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Sync failed: {}", error_text));
+        return Err(match status.as_u16() {
+            401 => SyncError::Unauthorized(message),
+            429 => SyncError::RateLimited { retry_after, message },
+            code if status.is_server_error() => SyncError::Server { status: code, message },
+            _ => SyncError::Request(message),
+        });
     }
 
-    Ok(())
+    response
+        .json::<SyncResponse>()
+        .await
+        .map_err(|e| SyncError::Request(format!("Invalid sync response: {}", e)))
+}
+
+/// Parse a `Retry-After` header: either delay-seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
 }
 
 /// Manually trigger a calendar sync
 #[tauri::command]
 pub async fn trigger_calendar_sync(
+    service: State<'_, SyncService>,
     api_url: String,
-    auth_token: String,
 ) -> Result<(), String> {
-    let mut state = SYNC_STATE.lock().await;
-    
-    if state.is_syncing {
-        return Err("Sync already in progress".to_string());
-    }
-    
-    state.is_syncing = true;
-    state.error = None;
-    drop(state);
-
-    match sync_calendar(&api_url, &auth_token).await {
-        Ok(_) => {
-            let mut state = SYNC_STATE.lock().await;
-            state.is_syncing = false;
-            state.last_sync = Some(chrono::Utc::now().to_rfc3339());
-            Ok(())
-        }
-        Err(e) => {
-            let mut state = SYNC_STATE.lock().await;
-            state.is_syncing = false;
-            state.error = Some(e.clone());
-            Err(e)
-        }
-    }
+    let auth_token = auth::current_token(&service.ctx.app_handle)
+        .await
+        .ok_or_else(|| SyncError::NotLoggedIn.to_string())?;
+    let policy = RetryPolicy::MANUAL;
+    let config = service.config();
+    sync_with_retry(&service.ctx, &api_url, &auth_token, &config, &policy, None)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Get current sync status
@@ -113,9 +967,49 @@ pub async fn get_sync_status() -> Result<SyncStatus, String> {
     Ok(state.clone())
 }
 
-/// Start the background sync service
+/// Start the background sync service (no-op if it is already running)
 #[tauri::command]
-pub async fn start_sync_service(api_url: String, auth_token: String) -> Result<(), String> {
-    start_background_sync(api_url, auth_token).await;
+pub async fn start_sync_service(
+    service: State<'_, SyncService>,
+    api_url: String,
+) -> Result<(), String> {
+    service.start(api_url).await;
     Ok(())
 }
+
+/// Stop the background sync service
+#[tauri::command]
+pub async fn stop_sync_service(service: State<'_, SyncService>) -> Result<(), String> {
+    service.stop().await;
+    Ok(())
+}
+
+/// Restart the background sync service, optionally against a new API URL
+#[tauri::command]
+pub async fn restart_sync_service(
+    service: State<'_, SyncService>,
+    api_url: Option<String>,
+) -> Result<(), String> {
+    service.restart(api_url).await
+}
+
+/// Get the sync configuration
+#[tauri::command]
+pub fn get_sync_config(service: State<'_, SyncService>) -> SyncConfig {
+    service.config()
+}
+
+/// Update the sync configuration (persisted and applied to the running loop)
+#[tauri::command]
+pub fn set_sync_config(service: State<'_, SyncService>, config: SyncConfig) -> Result<(), String> {
+    service.update_config(config)
+}
+
+/// Get the most recent sync attempts, newest first
+#[tauri::command]
+pub fn get_sync_history(
+    service: State<'_, SyncService>,
+    limit: Option<usize>,
+) -> Vec<SyncAttempt> {
+    service.ctx.history.recent(limit.unwrap_or(SYNC_HISTORY_LIMIT))
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Duration};
+use url::Url;
+
+/// Host and port probed to decide whether the API is reachable
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbeTarget {
+    pub host: String,
+    pub port: u16,
+}
+
+impl ProbeTarget {
+    /// Probe the host serving `url` (e.g. the API base URL)
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| format!("URL has no host: {}", url))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = parsed
+            .port_or_known_default()
+            .ok_or_else(|| format!("URL has no port: {}", url))?;
+        Ok(Self { host, port })
+    }
+}
+
+/// How often, and how patiently, the monitor probes
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorConfig {
+    /// Delay between probes while online
+    pub online_interval: Duration,
+    /// Delay between probes while offline, short so reconnects are noticed quickly
+    pub offline_interval: Duration,
+    /// Give up on a probe after this long
+    pub probe_timeout: Duration,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            online_interval: Duration::from_secs(60),
+            offline_interval: Duration::from_secs(5),
+            probe_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// Whether a TCP connection to `target` can be opened within `probe_timeout`
+pub async fn probe(target: &ProbeTarget, probe_timeout: Duration) -> bool {
+    let connect = TcpStream::connect((target.host.as_str(), target.port));
+    matches!(timeout(probe_timeout, connect).await, Ok(Ok(_)))
+}
+
+/// Watches connectivity to the API in the background.
+/// The probe task stops when the monitor is dropped.
+pub struct ConnectivityMonitor {
+    online_tx: Arc<watch::Sender<bool>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConnectivityMonitor {
+    /// Start probing `target`. Assumes online until the first probe says otherwise.
+    pub fn start(target: ProbeTarget, config: MonitorConfig) -> Self {
+        let online_tx = Arc::new(watch::channel(true).0);
+        let tx = online_tx.clone();
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let online = probe(&target, config.probe_timeout).await;
+                tx.send_if_modified(|current| {
+                    if *current == online {
+                        return false;
+                    }
+                    println!(
+                        "Connectivity to {}:{} {}",
+                        target.host,
+                        target.port,
+                        if online { "restored" } else { "lost" }
+                    );
+                    *current = online;
+                    true
+                });
+
+                let wait = if online {
+                    config.online_interval
+                } else {
+                    config.offline_interval
+                };
+                tokio::time::sleep(wait).await;
+            }
+        });
+
+        Self {
+            online_tx,
+            handle: Some(handle),
+        }
+    }
+
+    /// Monitor the host serving the API at `api_url`
+    pub fn for_api(api_url: &str) -> Result<Self, String> {
+        Ok(Self::start(
+            ProbeTarget::from_url(api_url)?,
+            MonitorConfig::default(),
+        ))
+    }
+
+    /// A monitor that never probes and always reports online
+    pub fn always_online() -> Self {
+        Self {
+            online_tx: Arc::new(watch::channel(true).0),
+            handle: None,
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        *self.online_tx.borrow()
+    }
+
+    /// Receive connectivity changes (`true` = online)
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.online_tx.subscribe()
+    }
+}
+
+impl Drop for ConnectivityMonitor {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn fast_config() -> MonitorConfig {
+        MonitorConfig {
+            online_interval: Duration::from_millis(20),
+            offline_interval: Duration::from_millis(20),
+            probe_timeout: Duration::from_millis(200),
+        }
+    }
+
+    /// Bind a local stand-in for the API server
+    async fn stand_in_server() -> (TcpListener, ProbeTarget) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let target = ProbeTarget {
+            host: "127.0.0.1".to_string(),
+            port,
+        };
+        (listener, target)
+    }
+
+    async fn wait_for(rx: &mut watch::Receiver<bool>, expected: bool) {
+        timeout(Duration::from_secs(2), rx.wait_for(|online| *online == expected))
+            .await
+            .expect("connectivity did not change in time")
+            .unwrap();
+    }
+
+    #[test]
+    fn probe_target_from_api_url() {
+        let target = ProbeTarget::from_url("https://api.miniorg.app/path").unwrap();
+        assert_eq!(target.host, "api.miniorg.app");
+        assert_eq!(target.port, 443);
+
+        let target = ProbeTarget::from_url("http://localhost:8788").unwrap();
+        assert_eq!(target.host, "localhost");
+        assert_eq!(target.port, 8788);
+
+        assert!(ProbeTarget::from_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn probe_reaches_stand_in_server() {
+        let (listener, target) = stand_in_server().await;
+        assert!(probe(&target, Duration::from_millis(200)).await);
+
+        drop(listener);
+        assert!(!probe(&target, Duration::from_millis(200)).await);
+    }
+
+    #[tokio::test]
+    async fn monitor_reports_offline_and_reconnect() {
+        let (listener, target) = stand_in_server().await;
+        let addr = listener.local_addr().unwrap();
+        let monitor = ConnectivityMonitor::start(target, fast_config());
+        let mut rx = monitor.subscribe();
+        assert!(monitor.is_online());
+
+        drop(listener);
+        wait_for(&mut rx, false).await;
+        assert!(!monitor.is_online());
+
+        let _listener = TcpListener::bind(addr).await.unwrap();
+        wait_for(&mut rx, true).await;
+        assert!(monitor.is_online());
+    }
+
+    #[tokio::test]
+    async fn always_online_never_changes() {
+        let monitor = ConnectivityMonitor::always_online();
+        assert!(monitor.is_online());
+        assert!(!monitor.subscribe().has_changed().unwrap());
+    }
+}
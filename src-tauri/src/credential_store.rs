@@ -0,0 +1,353 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use keyring::Entry;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::auth::AuthError;
+
+const CREDENTIALS_FILE: &str = "credentials.enc";
+const CREDENTIALS_KEY_FILE: &str = "credentials.key";
+const CREDENTIALS_FILE_VERSION: u32 = 1;
+/// Entry looked up to check that the keyring answers at all
+const PROBE_KEY: &str = "probe";
+
+/// Where the auth module keeps its secrets (tokens, account list)
+pub trait CredentialStore: Send + Sync {
+    /// Short backend name, for logs and the frontend
+    fn name(&self) -> &'static str;
+    /// `Ok(None)` only when nothing is stored under `key`; any failure is an error
+    fn get(&self, key: &str) -> Result<Option<String>, AuthError>;
+    fn set(&self, key: &str, value: &str) -> Result<(), AuthError>;
+    /// Deleting a missing key is not an error
+    fn delete(&self, key: &str) -> Result<(), AuthError>;
+}
+
+/// The OS credential store (macOS Keychain, Secret Service, Windows Credential Manager)
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+
+    fn entry(&self, key: &str) -> Result<Entry, AuthError> {
+        Entry::new(&self.service, key).map_err(|e| keyring_error("Keyring unavailable", e))
+    }
+
+    /// Whether the platform keyring can be used. Only a `PlatformFailure` (no backend
+    /// to talk to) rules it out: a locked keyring still counts, so reads and writes
+    /// report `Locked` instead of the app moving to the file store for good.
+    pub fn probe(&self) -> Result<(), AuthError> {
+        let result = Entry::new(&self.service, PROBE_KEY).and_then(|entry| entry.get_password());
+        match result {
+            Err(e @ keyring::Error::PlatformFailure(_)) => {
+                Err(keyring_error("Keyring probe failed", e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Classify a keyring error by its variant; the platform backends map their own codes
+/// (e.g. a locked keychain or Secret Service collection is `NoStorageAccess`)
+fn keyring_error(context: &str, error: keyring::Error) -> AuthError {
+    let message = format!("{}: {}", context, error);
+    match error {
+        keyring::Error::NoEntry => AuthError::NotFound(message),
+        keyring::Error::NoStorageAccess(_) => AuthError::Locked(message),
+        keyring::Error::BadEncoding(_) => AuthError::Corrupt(message),
+        _ => AuthError::Backend(message),
+    }
+}
+
+/// A file the process isn't allowed to touch is `Denied`, anything else `Backend`
+fn io_error(context: &str, error: std::io::Error) -> AuthError {
+    let message = format!("{}: {}", context, error);
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied => AuthError::Denied(message),
+        _ => AuthError::Backend(message),
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(keyring_error(
+                &format!("Failed to read {} from keyring", key),
+                e,
+            )),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        self.entry(key)?
+            .set_password(value)
+            .map_err(|e| keyring_error(&format!("Failed to write {} to keyring", key), e))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AuthError> {
+        match self.entry(key)?.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keyring_error(
+                &format!("Failed to delete {} from keyring", key),
+                e,
+            )),
+        }
+    }
+}
+
+/// Fallback for systems without a usable keyring (e.g. Linux without a Secret Service).
+/// All entries are encrypted together with XChaCha20-Poly1305 under a random key kept in
+/// a separate owner-only file. That keeps them out of plain-text backups and casual reads,
+/// but unlike the keyring it doesn't protect them from other programs run by the same user.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    // Serializes read-modify-write of the file
+    lock: Mutex<()>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedFileStore {
+    /// Store in `dir` (the app data dir)
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(CREDENTIALS_FILE),
+            key_path: dir.join(CREDENTIALS_KEY_FILE),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self, create: bool) -> Result<XChaCha20Poly1305, AuthError> {
+        let key = match fs::read(&self.key_path) {
+            Ok(key) => key,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && create => {
+                let mut key = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                write_private(&self.key_path, &key)?;
+                key
+            }
+            // The credentials file can't be read back without its key
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(AuthError::Corrupt(
+                    "Credential key file is missing".to_string(),
+                ))
+            }
+            Err(e) => return Err(io_error("Failed to read credential key", e)),
+        };
+        if key.len() != 32 {
+            return Err(AuthError::Corrupt(
+                "Credential key file is corrupt".to_string(),
+            ));
+        }
+        Ok(XChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+
+    /// Entries of the file. One that can't be decrypted or parsed as a whole is moved
+    /// to `*.quarantine` with its key and reads as empty, so a new login can be stored.
+    fn load(&self) -> Result<HashMap<String, String>, AuthError> {
+        match self.decode() {
+            Err(e @ AuthError::Corrupt(_)) => {
+                eprintln!("Quarantining credentials file: {}", e);
+                quarantine_file(&self.path)?;
+                quarantine_file(&self.key_path)?;
+                Ok(HashMap::new())
+            }
+            result => result,
+        }
+    }
+
+    fn decode(&self) -> Result<HashMap<String, String>, AuthError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(io_error("Failed to read credentials file", e)),
+        };
+        let corrupt = |e: &dyn std::fmt::Display| {
+            AuthError::Corrupt(format!("Credentials file is corrupt: {}", e))
+        };
+
+        let file = serde_json::from_str::<EncryptedFile>(&contents).map_err(|e| corrupt(&e))?;
+        if file.version != CREDENTIALS_FILE_VERSION {
+            return Err(AuthError::Corrupt(format!(
+                "Unsupported credentials file version {}",
+                file.version
+            )));
+        }
+        let nonce = STANDARD.decode(&file.nonce).map_err(|e| corrupt(&e))?;
+        let ciphertext = STANDARD.decode(&file.ciphertext).map_err(|e| corrupt(&e))?;
+        if nonce.len() != 24 {
+            return Err(corrupt(&"bad nonce"));
+        }
+
+        let plaintext = self
+            .cipher(false)?
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| {
+                AuthError::Corrupt(
+                    "Failed to decrypt credentials file (corrupt or wrong key)".to_string(),
+                )
+            })?;
+        serde_json::from_slice(&plaintext).map_err(|e| corrupt(&e))
+    }
+
+    fn save(&self, entries: &HashMap<String, String>) -> Result<(), AuthError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| io_error("Failed to create data dir", e))?;
+        }
+
+        let plaintext =
+            serde_json::to_vec(entries).map_err(|e| AuthError::Backend(e.to_string()))?;
+        let mut nonce = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher(true)?
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| AuthError::Backend("Failed to encrypt credentials".to_string()))?;
+
+        let file = EncryptedFile {
+            version: CREDENTIALS_FILE_VERSION,
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let contents =
+            serde_json::to_vec_pretty(&file).map_err(|e| AuthError::Backend(e.to_string()))?;
+        write_private(&self.path, &contents)
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "encrypted-file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, AuthError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(self.load()?.remove(key))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), AuthError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.load()?;
+        entries.insert(key.to_string(), value.to_string());
+        self.save(&entries)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AuthError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.load()?;
+        if entries.remove(key).is_some() {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+}
+
+/// Move `path` to `<path>.quarantine` (replacing an older one), if it exists
+fn quarantine_file(path: &Path) -> Result<(), AuthError> {
+    let mut target = path.as_os_str().to_owned();
+    target.push(".quarantine");
+    match fs::rename(path, &target) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(io_error(
+            &format!("Failed to quarantine {}", path.display()),
+            e,
+        )),
+    }
+}
+
+/// Write through a temporary file so a crash never leaves a half-written file,
+/// readable by the owner only
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), AuthError> {
+    let tmp_path = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&tmp_path)
+        .map_err(|e| io_error(&format!("Failed to write {}", tmp_path.display()), e))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| io_error(&format!("Failed to write {}", tmp_path.display()), e))?;
+    fs::rename(&tmp_path, path)
+        .map_err(|e| io_error(&format!("Failed to write {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "miniorg-{}-{}-{}",
+            name,
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn corrupt_file_is_quarantined_and_store_stays_usable() {
+        let dir = temp_dir("corrupt-credentials");
+        let store = EncryptedFileStore::new(&dir);
+        store.set("auth_token", "old").unwrap();
+        fs::write(dir.join(CREDENTIALS_FILE), b"not json at all").unwrap();
+
+        assert_eq!(store.get("auth_token"), Ok(None));
+        assert!(dir.join("credentials.enc.quarantine").exists());
+        assert!(dir.join("credentials.key.quarantine").exists());
+
+        store.set("auth_token", "new").unwrap();
+        assert_eq!(store.get("auth_token"), Ok(Some("new".to_string())));
+        store.delete("auth_token").unwrap();
+        assert_eq!(store.get("auth_token"), Ok(None));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_key_file_is_quarantined() {
+        let dir = temp_dir("missing-key");
+        let store = EncryptedFileStore::new(&dir);
+        store.set("auth_token", "old").unwrap();
+        fs::remove_file(dir.join(CREDENTIALS_KEY_FILE)).unwrap();
+
+        assert_eq!(store.get("auth_token"), Ok(None));
+        store.set("auth_token", "new").unwrap();
+        assert_eq!(store.get("auth_token"), Ok(Some("new".to_string())));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
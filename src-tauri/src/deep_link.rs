@@ -0,0 +1,194 @@
+use std::sync::Mutex;
+
+use chrono::NaiveDate;
+use tauri::{AppHandle, Emitter, Manager, State};
+use url::Url;
+
+use crate::auth;
+
+const SCHEME: &str = "miniorg";
+
+/// App pages that `miniorg://<page>` may open directly
+/// (e.g. `miniorg://settings/calendars?onboarding=true` after connecting a calendar)
+const PAGES: &[&str] = &[
+    "calendar",
+    "backlog",
+    "daily-planning",
+    "daily-wrapup",
+    "settings",
+];
+
+/// A `miniorg://` URL, resolved to what the app should do with it
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    /// `miniorg://open` - bring the app to the front
+    Open,
+    /// `miniorg://oauth/callback?code=...&state=...`
+    OAuthCallback(String),
+    /// `miniorg://task/<id>`
+    Task(String),
+    /// `miniorg://day/2026-10-17`
+    Day(NaiveDate),
+    /// `miniorg://add?title=...`
+    AddTask { title: String },
+    /// `miniorg://<page>/...?...` - path and query of an app page
+    Page(String),
+}
+
+/// Events for the dashboard (`navigate-to`, `deep-link-error`) held back while it
+/// isn't listening - e.g. for the link that launched the app, or while on `/login` -
+/// and emitted when it calls `deep_link_ready`
+#[derive(Default)]
+pub struct DeepLinkQueue {
+    state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    ready: bool,
+    /// Event name and payload
+    pending: Vec<(&'static str, String)>,
+}
+
+impl DeepLinkQueue {
+    /// Emit `event` to the main window now, or once it is listening
+    fn emit(&self, app: &AppHandle, event: &'static str, payload: String) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.ready {
+            drop(state);
+            let _ = app.emit_to("main", event, payload);
+        } else {
+            println!("Main window not listening, {} queued", event);
+            state.pending.push((event, payload));
+        }
+    }
+
+    fn set_ready(&self, ready: bool) -> Vec<(&'static str, String)> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.ready = ready;
+        if ready {
+            std::mem::take(&mut state.pending)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl DeepLink {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid deep link {}: {}", url, e))?;
+        if parsed.scheme() != SCHEME {
+            return Err(format!("Not a {}:// link: {}", SCHEME, url));
+        }
+
+        // miniorg://task/123 parses as host "task", path "/123"
+        let host = parsed.host_str().unwrap_or_default();
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        let param = |name: &str| {
+            parsed
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.trim().to_string())
+        };
+
+        match (host, segments.as_slice()) {
+            ("open" | "", []) => Ok(Self::Open),
+            ("oauth", ["callback"]) => Ok(Self::OAuthCallback(url.to_string())),
+            ("task", [id]) if is_valid_id(id) => Ok(Self::Task(id.to_string())),
+            ("day", [date]) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(Self::Day)
+                .map_err(|_| format!("Invalid date in deep link: {}", date)),
+            ("add", []) => match param("title") {
+                Some(title) if !title.is_empty() => Ok(Self::AddTask { title }),
+                _ => Err("Deep link to add a task needs a title".to_string()),
+            },
+            (page, _) if PAGES.contains(&page) => {
+                let mut path = format!("/{}{}", page, parsed.path());
+                if let Some(query) = parsed.query() {
+                    path.push('?');
+                    path.push_str(query);
+                }
+                Ok(Self::Page(path))
+            }
+            _ => Err(format!("Unknown deep link: {}", url)),
+        }
+    }
+
+    /// Path to open in the main window, if the link navigates
+    fn path(&self) -> Option<String> {
+        match self {
+            Self::Task(id) => Some(format!("/backlog?task={}", id)),
+            Self::Day(date) => Some(format!("/daily-planning?date={}", date.format("%Y-%m-%d"))),
+            Self::Page(path) => Some(path.clone()),
+            Self::Open | Self::OAuthCallback(_) | Self::AddTask { .. } => None,
+        }
+    }
+}
+
+/// Task ids are cuids; anything else would end up in a URL unchecked
+fn is_valid_id(id: &str) -> bool {
+    id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Route a `miniorg://` URL: focus the main window, then navigate or hand an OAuth
+/// callback to `auth`; `add` links open a pre-filled quick-add panel instead.
+/// Unknown or malformed links only emit `deep-link-error` to the main window.
+/// Events for the dashboard go through `DeepLinkQueue`, so they wait until it listens.
+pub fn handle(app: &AppHandle, url: &str) {
+    println!("Deep link received: {}", url);
+
+    let link = match DeepLink::parse(url) {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("{}", e);
+            emit_to_dashboard(app, "deep-link-error", e);
+            return;
+        }
+    };
+
+    match link {
+        // Never create a task straight from a link: the user confirms it in the quick-add panel
+        DeepLink::AddTask { title } => {
+            if let Err(e) = crate::open_quick_add_panel(app, Some(title)) {
+                eprintln!("Failed to open quick add for deep link: {}", e);
+            }
+        }
+        link => {
+            if let Err(e) = crate::focus_main_window(app.clone(), None) {
+                eprintln!("Failed to focus main window for deep link: {}", e);
+            }
+            if let Some(path) = link.path() {
+                emit_to_dashboard(app, "navigate-to", path);
+            }
+            if let DeepLink::OAuthCallback(url) = link {
+                auth::handle_deep_link(app, url);
+            }
+        }
+    }
+}
+
+fn emit_to_dashboard(app: &AppHandle, event: &'static str, payload: String) {
+    match app.try_state::<DeepLinkQueue>() {
+        Some(queue) => queue.emit(app, event, payload),
+        None => {
+            let _ = app.emit_to("main", event, payload);
+        }
+    }
+}
+
+/// Called by the dashboard once its deep-link listeners are registered
+#[tauri::command]
+pub fn deep_link_ready(app: AppHandle, queue: State<'_, DeepLinkQueue>) {
+    for (event, payload) in queue.set_ready(true) {
+        let _ = app.emit_to("main", event, payload);
+    }
+}
+
+/// Called by the dashboard when it removes its listeners (e.g. on logout)
+#[tauri::command]
+pub fn deep_link_closed(queue: State<'_, DeepLinkQueue>) {
+    queue.set_ready(false);
+}
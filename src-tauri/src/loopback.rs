@@ -0,0 +1,319 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use url::{form_urlencoded, Url};
+
+/// Largest request head (request line + headers) we accept
+const MAX_HEAD_BYTES: usize = 32 * 1024;
+/// Largest request body we accept (form_post callbacks are small)
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// An HTTP/1.x request received by the loopback listener
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// First header with this name (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parsed request target, resolved against the loopback origin
+    pub fn url(&self) -> Option<Url> {
+        Url::parse("http://127.0.0.1").ok()?.join(&self.target).ok()
+    }
+
+    fn is_form(&self) -> bool {
+        self.header("content-type")
+            .and_then(|value| value.split(';').next())
+            .map(|mime| {
+                mime.trim()
+                    .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Read one request: the head up to the blank line, then `Content-Length` bytes of body.
+/// Works regardless of how the bytes are split across TCP reads.
+pub async fn read_request<R: AsyncRead + Unpin>(reader: &mut R) -> Result<HttpRequest, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        let head_end = find_head_end(&buffer);
+        if head_end.unwrap_or(buffer.len()) > MAX_HEAD_BYTES {
+            return Err("Request head too large".to_string());
+        }
+        if let Some(pos) = head_end {
+            break pos;
+        }
+        let n = reader.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed before end of headers".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..head_end])
+        .map_err(|_| "Request head is not valid UTF-8".to_string())?;
+    let mut lines = head.split("\r\n");
+
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), target.to_string())
+        }
+        _ => return Err(format!("Malformed request line: {}", request_line)),
+    };
+
+    let mut headers = Vec::new();
+    for line in lines {
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("Malformed header: {}", line))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = HttpRequest {
+        method,
+        target,
+        headers,
+        body: Vec::new(),
+    };
+
+    let content_length = match request.header("content-length") {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid Content-Length: {}", value))?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let n = reader.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed before end of body".to_string());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    request.body = body;
+
+    Ok(request)
+}
+
+fn find_head_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+/// Parameters of an OAuth redirect to the loopback listener
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+impl CallbackParams {
+    /// Extract the callback from a request to `path`: the query string of a GET,
+    /// or the form body of a POST (`response_mode=form_post`).
+    /// Returns `None` for any other path or method.
+    pub fn from_request(request: &HttpRequest, path: &str) -> Option<Self> {
+        let url = request.url()?;
+        if url.path() != path {
+            return None;
+        }
+
+        let pairs: Vec<(String, String)> = match request.method.as_str() {
+            "GET" => url.query_pairs().into_owned().collect(),
+            "POST" if request.is_form() => {
+                form_urlencoded::parse(&request.body).into_owned().collect()
+            }
+            _ => return None,
+        };
+        let param = |name: &str| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        Some(Self {
+            code: param("code"),
+            state: param("state"),
+            error: param("error"),
+            error_description: param("error_description"),
+        })
+    }
+}
+
+/// Write a complete HTML response and close the write side
+pub async fn write_response<W: AsyncWrite + Unpin>(writer: &mut W, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = writer.write_all(response.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time::{sleep, Duration};
+
+    /// Send `chunks` from a local TCP client, pausing between them so they arrive
+    /// as separate reads, and parse what the server side receives
+    async fn send_in_chunks(chunks: Vec<Vec<u8>>) -> Result<HttpRequest, String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            for chunk in chunks {
+                // The server may stop reading early (e.g. oversized requests)
+                if stream.write_all(&chunk).await.is_err() {
+                    break;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+            // Dropping the stream closes the connection, ending incomplete requests
+        });
+
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let request = read_request(&mut socket).await;
+        client.await.unwrap();
+        request
+    }
+
+    async fn send(raw: &str) -> Result<HttpRequest, String> {
+        send_in_chunks(vec![raw.as_bytes().to_vec()]).await
+    }
+
+    #[tokio::test]
+    async fn parses_get_callback_query() {
+        let request =
+            send("GET /callback?code=4%2F0Abc&state=xyz HTTP/1.1\r\nHost: 127.0.0.1:5000\r\n\r\n")
+                .await
+                .unwrap();
+
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.header("host"), Some("127.0.0.1:5000"));
+        let params = CallbackParams::from_request(&request, "/callback").unwrap();
+        assert_eq!(params.code.as_deref(), Some("4/0Abc"));
+        assert_eq!(params.state.as_deref(), Some("xyz"));
+        assert_eq!(params.error, None);
+    }
+
+    #[tokio::test]
+    async fn parses_request_split_across_reads() {
+        let raw = b"GET /callback?code=abc&state=s1 HTTP/1.1\r\nHost: 127.0.0.1\r\nAccept: text/html\r\n\r\n";
+        let chunks = raw.chunks(7).map(|chunk| chunk.to_vec()).collect();
+
+        let request = send_in_chunks(chunks).await.unwrap();
+        let params = CallbackParams::from_request(&request, "/callback").unwrap();
+        assert_eq!(params.code.as_deref(), Some("abc"));
+        assert_eq!(params.state.as_deref(), Some("s1"));
+        assert_eq!(request.header("ACCEPT"), Some("text/html"));
+    }
+
+    #[tokio::test]
+    async fn parses_query_larger_than_one_read() {
+        let state = "s".repeat(10_000);
+        let raw = format!("GET /callback?code=abc&state={} HTTP/1.1\r\n\r\n", state);
+
+        let request = send(&raw).await.unwrap();
+        let params = CallbackParams::from_request(&request, "/callback").unwrap();
+        assert_eq!(params.state, Some(state));
+    }
+
+    #[tokio::test]
+    async fn parses_form_post_body_after_headers() {
+        let body = "state=xyz&error=access_denied&error_description=User+denied";
+        let head = format!(
+            "POST /callback HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded; charset=utf-8\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        // Body arrives in a later read than the headers
+        let chunks = vec![head.into_bytes(), body[..10].into(), body[10..].into()];
+
+        let request = send_in_chunks(chunks).await.unwrap();
+        assert_eq!(request.body, body.as_bytes());
+        let params = CallbackParams::from_request(&request, "/callback").unwrap();
+        assert_eq!(params.state.as_deref(), Some("xyz"));
+        assert_eq!(params.error.as_deref(), Some("access_denied"));
+        assert_eq!(params.error_description.as_deref(), Some("User denied"));
+        assert_eq!(params.code, None);
+    }
+
+    #[tokio::test]
+    async fn ignores_other_paths_and_methods() {
+        let favicon = send("GET /favicon.ico HTTP/1.1\r\n\r\n").await.unwrap();
+        assert_eq!(CallbackParams::from_request(&favicon, "/callback"), None);
+
+        let put = send("PUT /callback?code=abc HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        assert_eq!(CallbackParams::from_request(&put, "/callback"), None);
+
+        // A POST that isn't a form has no callback parameters to read
+        let json = send("POST /callback HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}")
+            .await
+            .unwrap();
+        assert_eq!(CallbackParams::from_request(&json, "/callback"), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_incomplete_or_malformed_requests() {
+        assert!(send("GET /callback?code=abc HTTP/1.1\r\nHost: x\r\n")
+            .await
+            .is_err());
+        assert!(send("not http\r\n\r\n").await.is_err());
+        assert!(
+            send("POST /callback HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort")
+                .await
+                .is_err()
+        );
+        assert!(
+            send("POST /callback HTTP/1.1\r\nContent-Length: nope\r\n\r\n")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_head() {
+        let raw = format!(
+            "GET /callback?state={} HTTP/1.1\r\n\r\n",
+            "s".repeat(MAX_HEAD_BYTES + 1)
+        );
+        assert_eq!(send(&raw).await.unwrap_err(), "Request head too large");
+    }
+
+    #[tokio::test]
+    async fn writes_complete_response() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        write_response(&mut server, "200 OK", "<p>ok</p>").await;
+
+        let mut received = String::new();
+        client.read_to_string(&mut received).await.unwrap();
+        assert!(received.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received.contains("Content-Length: 9\r\n"));
+        assert!(received.ends_with("\r\n\r\n<p>ok</p>"));
+    }
+}
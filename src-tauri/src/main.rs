@@ -4,7 +4,13 @@
 mod auth;
 mod notifications;
 mod calendar_sync;
+mod connectivity;
+mod credential_store;
+mod deep_link;
+mod loopback;
+mod oauth_page;
 
+use serde::Serialize;
 use tauri::{
     Emitter,
     Manager,
@@ -74,19 +80,31 @@ fn move_panel_to_mouse_screen(app: &AppHandle) {
     }
 }
 
-#[tauri::command]
-fn show_quick_add_window(app: AppHandle) -> Result<(), String> {
+/// Payload of `open-quick-add`: a title to pre-fill (e.g. from `miniorg://add?title=...`)
+#[derive(Debug, Clone, Default, Serialize)]
+struct OpenQuickAddPayload {
+    title: Option<String>,
+}
+
+/// Show the quick-add panel on the screen with the mouse, optionally pre-filled
+fn open_quick_add_panel(app: &AppHandle, title: Option<String>) -> Result<(), String> {
     if let Ok(panel) = app.get_webview_panel("quick-add") {
-        move_panel_to_mouse_screen(&app);
+        move_panel_to_mouse_screen(app);
         panel.show();
         panel.make_key_window();
-        app.emit("open-quick-add", ()).map_err(|e| e.to_string())?;
+        app.emit("open-quick-add", OpenQuickAddPayload { title })
+            .map_err(|e| e.to_string())?;
         Ok(())
     } else {
         Err("Quick add panel not found".to_string())
     }
 }
 
+#[tauri::command]
+fn show_quick_add_window(app: AppHandle) -> Result<(), String> {
+    open_quick_add_panel(&app, None)
+}
+
 #[tauri::command]
 fn hide_quick_add_window(app: AppHandle) -> Result<(), String> {
     if let Ok(panel) = app.get_webview_panel("quick-add") {
@@ -125,29 +143,73 @@ fn toggle_quick_add_window(app: AppHandle) {
         if panel.is_visible() {
             panel.hide();
         } else {
-            #[cfg(target_os = "macos")]
-            move_panel_to_mouse_screen(&app);
-            panel.show();
-            panel.make_key_window();
-            let _ = app.emit("open-quick-add", ());
+            let _ = open_quick_add_panel(&app, None);
         }
     }
 }
 
+/// A second launch exits right away and hands its arguments to the running instance:
+/// deep links go through the router, a plain relaunch just brings the app forward
+fn forward_second_instance(app: &AppHandle, argv: Vec<String>) {
+    let links: Vec<&String> = argv
+        .iter()
+        .skip(1)
+        .filter(|arg| arg.starts_with("miniorg://"))
+        .collect();
+
+    if links.is_empty() {
+        deep_link::handle(app, "miniorg://open");
+    }
+    for url in links {
+        deep_link::handle(app, url);
+    }
+}
+
 fn main() {
     tauri::Builder::default()
+        // Must be registered first, so a second instance exits before setting anything up
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            forward_second_instance(app, argv);
+        }))
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_nspanel::init())
         .setup(|app| {
-            // Handle deep links - focus the main window when a deep link is received
+            let app_data_dir = app.path().app_data_dir()?;
+
+            // Tokens go to the OS keyring, or an encrypted file when there is none
+            auth::init_credential_store(&app_data_dir);
+            app.manage(auth::AuthStore::new(app.handle().clone()));
+
+            // Background calendar sync service (at most one loop per app)
+            app.manage(calendar_sync::SyncService::new(
+                app.handle().clone(),
+                app_data_dir.join(calendar_sync::SYNC_CONFIG_FILE),
+                app_data_dir.join(calendar_sync::SYNC_HISTORY_FILE),
+            ));
+
+            // Route miniorg:// deep links (focus, navigation, quick add, OAuth callbacks);
+            // navigation waits until the dashboard is listening
+            app.manage(deep_link::DeepLinkQueue::default());
             #[cfg(desktop)]
-            app.deep_link().on_open_url(|event| {
-                // Deep link received, the app will be focused automatically
-                // The URL is available in event.urls() if we need to handle specific routes
-            });
+            {
+                let app_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        deep_link::handle(&app_handle, url.as_str());
+                    }
+                });
+
+                // On Windows/Linux the link that launched the app is only in argv
+                #[cfg(any(windows, target_os = "linux"))]
+                if let Ok(Some(urls)) = app.deep_link().get_current() {
+                    for url in urls {
+                        deep_link::handle(app.handle(), url.as_str());
+                    }
+                }
+            }
 
             // Convert the quick-add window to a panel so it can appear over fullscreen apps
             if let Some(quick_add_window) = app.get_webview_window("quick-add") {
@@ -206,20 +268,45 @@ fn main() {
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // Refresh calendars when the main window comes back to the foreground
+            if let tauri::WindowEvent::Focused(true) = event {
+                if window.label() == "main" {
+                    if let Some(service) = window.try_state::<calendar_sync::SyncService>() {
+                        service.request_sync();
+                    }
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             auth::start_oauth_flow,
             auth::get_auth_token,
             auth::set_auth_token,
             auth::clear_auth_token,
+            auth::list_accounts,
+            auth::get_session_info,
+            auth::switch_account,
+            auth::get_credential_store_status,
             auth::start_oauth_listener,
+            auth::cancel_oauth_listener,
+            auth::build_oauth_url,
+            auth::exchange_oauth_code,
+            auth::start_token_refresh,
             notifications::send_notification,
             notifications::request_notification_permission,
             calendar_sync::trigger_calendar_sync,
             calendar_sync::get_sync_status,
             calendar_sync::start_sync_service,
+            calendar_sync::stop_sync_service,
+            calendar_sync::restart_sync_service,
+            calendar_sync::get_sync_config,
+            calendar_sync::set_sync_config,
+            calendar_sync::get_sync_history,
             show_quick_add_window,
             hide_quick_add_window,
             focus_main_window,
+            deep_link::deep_link_ready,
+            deep_link::deep_link_closed,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
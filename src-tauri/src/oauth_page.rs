@@ -0,0 +1,254 @@
+/// Language of the page shown in the browser after the OAuth redirect
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageLocale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl PageLocale {
+    /// Pick the page language from a BCP 47 tag such as `fr-FR` (English otherwise)
+    pub fn from_tag(tag: Option<&str>) -> Self {
+        match tag.map(|tag| tag.trim().to_ascii_lowercase()) {
+            Some(tag) if tag == "fr" || tag.starts_with("fr-") || tag.starts_with("fr_") => {
+                Self::Fr
+            }
+            _ => Self::En,
+        }
+    }
+
+    fn lang(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Fr => "fr",
+        }
+    }
+}
+
+struct PageText {
+    success_message: &'static str,
+    success_hint: &'static str,
+    error_message: &'static str,
+    error_hint: &'static str,
+    open_app: &'static str,
+}
+
+fn text(locale: PageLocale) -> PageText {
+    match locale {
+        PageLocale::En => PageText {
+            success_message: "Signed in successfully",
+            success_hint: "You can close this tab",
+            error_message: "Sign-in failed",
+            error_hint: "Go back to the app to try again",
+            open_app: "Open MiniOrg",
+        },
+        PageLocale::Fr => PageText {
+            success_message: "Connexion réussie",
+            success_hint: "Vous pouvez fermer cet onglet",
+            error_message: "Échec de la connexion",
+            error_hint: "Retournez dans l'application pour réessayer",
+            open_app: "Ouvrir MiniOrg",
+        },
+    }
+}
+
+const CHECK_ICON: &str = r#"<path stroke-linecap="round" stroke-linejoin="round" d="M5 13l4 4L19 7"/>"#;
+const CROSS_ICON: &str = r#"<path stroke-linecap="round" stroke-linejoin="round" d="M6 6l12 12M18 6L6 18"/>"#;
+
+/// Page for a callback that was accepted and forwarded to the app
+pub fn success_page(locale: PageLocale) -> String {
+    let text = text(locale);
+    render(
+        locale,
+        "success",
+        CHECK_ICON,
+        text.success_message,
+        "",
+        text.success_hint,
+        text.open_app,
+    )
+}
+
+/// Page for a provider error or a rejected callback, showing `description`
+pub fn error_page(locale: PageLocale, description: &str) -> String {
+    let text = text(locale);
+    let detail = format!(r#"<p class="detail">{}</p>"#, escape_html(description));
+    render(
+        locale,
+        "error",
+        CROSS_ICON,
+        text.error_message,
+        &detail,
+        text.error_hint,
+        text.open_app,
+    )
+}
+
+fn render(
+    locale: PageLocale,
+    variant: &str,
+    icon: &str,
+    message: &str,
+    detail: &str,
+    hint: &str,
+    open_app: &str,
+) -> String {
+    PAGE_TEMPLATE
+        .replace("{{lang}}", locale.lang())
+        .replace("{{variant}}", variant)
+        .replace("{{icon}}", icon)
+        .replace("{{message}}", message)
+        .replace("{{hint}}", hint)
+        .replace("{{open_app}}", open_app)
+        // Last, so placeholders inside the description are left alone
+        .replace("{{detail}}", detail)
+}
+
+/// The description comes from the redirect URL, so it must not be able to inject markup
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Matches the MiniOrg design; colors are variables so the page follows the system theme
+const PAGE_TEMPLATE: &str = r#"
+<!doctype html>
+<html lang="{{lang}}">
+  <head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="color-scheme" content="light dark">
+    <title>MiniOrg</title>
+    <style>
+      :root {
+        --background: hsl(40 10% 98%);
+        --foreground: hsl(0 0% 12%);
+        --muted: hsl(0 0% 45%);
+        --card: white;
+        --border: hsl(0 0% 90%);
+        --hover: hsl(0 0% 96%);
+        --success: hsl(142 76% 36%);
+        --error: hsl(0 72% 51%);
+      }
+      @media (prefers-color-scheme: dark) {
+        :root {
+          --background: hsl(0 0% 9%);
+          --foreground: hsl(0 0% 95%);
+          --muted: hsl(0 0% 64%);
+          --card: hsl(0 0% 13%);
+          --border: hsl(0 0% 22%);
+          --hover: hsl(0 0% 17%);
+        }
+      }
+      * { box-sizing: border-box; }
+      body {
+        font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+        display: flex;
+        justify-content: center;
+        align-items: center;
+        min-height: 100vh;
+        margin: 0;
+        background: var(--background);
+        color: var(--foreground);
+      }
+      .container {
+        width: 100%;
+        max-width: 28rem;
+        padding: 2rem;
+        text-align: center;
+      }
+      .icon {
+        width: 48px;
+        height: 48px;
+        margin: 0 auto 1.5rem;
+        background: var(--success);
+        border-radius: 50%;
+        display: flex;
+        align-items: center;
+        justify-content: center;
+      }
+      .error .icon {
+        background: var(--error);
+      }
+      .icon svg {
+        width: 24px;
+        height: 24px;
+        color: white;
+      }
+      h1 {
+        font-size: 2.25rem;
+        font-weight: 600;
+        letter-spacing: -0.025em;
+        margin: 0 0 0.5rem;
+      }
+      p {
+        color: var(--muted);
+        margin: 0 0 2.5rem;
+      }
+      .detail {
+        margin: -1.5rem 0 2.5rem;
+        padding: 0.75rem 1rem;
+        border: 1px solid var(--border);
+        border-radius: 0.5rem;
+        font-size: 0.875rem;
+        overflow-wrap: anywhere;
+      }
+      .btn {
+        display: flex;
+        align-items: center;
+        justify-content: center;
+        gap: 0.75rem;
+        width: 100%;
+        padding: 0.75rem 1rem;
+        background: var(--card);
+        border: 1px solid var(--border);
+        border-radius: 0.5rem;
+        font-size: 1rem;
+        font-weight: 500;
+        color: var(--foreground);
+        text-decoration: none;
+        box-shadow: 0 1px 2px rgba(0,0,0,0.05);
+        transition: background 0.2s;
+        cursor: pointer;
+      }
+      .btn:hover {
+        background: var(--hover);
+      }
+      .hint {
+        font-size: 0.875rem;
+        color: var(--muted);
+        margin-top: 1rem;
+      }
+    </style>
+  </head>
+  <body>
+    <div class="container {{variant}}">
+      <div class="icon">
+        <svg fill="none" stroke="currentColor" stroke-width="3" viewBox="0 0 24 24">
+          {{icon}}
+        </svg>
+      </div>
+      <h1>MiniOrg</h1>
+      <p>{{message}}</p>
+      {{detail}}
+      <a href="miniorg://open" class="btn">
+        <svg width="20" height="20" fill="none" stroke="currentColor" stroke-width="2" viewBox="0 0 24 24">
+          <path stroke-linecap="round" stroke-linejoin="round" d="M10 6H6a2 2 0 00-2 2v10a2 2 0 002 2h10a2 2 0 002-2v-4M14 4h6m0 0v6m0-6L10 14"/>
+        </svg>
+        {{open_app}}
+      </a>
+      <p class="hint">{{hint}}</p>
+    </div>
+  </body>
+</html>
+"#;